@@ -0,0 +1,209 @@
+use crate::data::*;
+
+/// A candidate on a list, with the number of preference votes cast for that candidate.
+/// Candidates that appear on several lists are recognised by their name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    pub name: String,
+    pub votes: Votes,
+}
+
+/// A candidate that has been elected, identified by its position on its list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elected {
+    pub list: usize,
+    pub position: usize,
+    /// Whether the candidate was elected based on preference votes
+    pub preference: bool,
+}
+
+/// The number of preference votes a candidate needs to be elected regardless of their position on
+/// the list: 25% of the quota, or 50% for bodies of less than 19 seats (i.e. municipal councils).
+/// Without seats there is no quota, and no threshold.
+pub fn preference_threshold(vote_count: Count, seat_count: Count) -> Option<Fraction> {
+    let share = if seat_count >= 19 {
+        frac(1, 4)
+    } else {
+        frac(1, 2)
+    };
+    Fraction::new(vote_count, seat_count)
+        .ok()?
+        .checked_mul(share)
+}
+
+/// The seats for parties that have the given lists of candidates, limited by the number of
+/// candidates on each list.
+pub fn candidate_limits(lists: &[Vec<Candidate>]) -> Vec<Seats> {
+    lists
+        .iter()
+        .map(|list| Seats::limited(list.len() as Count))
+        .collect()
+}
+
+/// The order in which the candidates on a list are considered for a seat: first those whose
+/// preference votes meet the `threshold`, by number of votes, and then the other candidates in
+/// the order of the list.
+pub fn ranking(list: &[Candidate], threshold: Fraction) -> Vec<usize> {
+    let meets_threshold = |i: &usize| frac(list[*i].votes.0, 1) >= threshold;
+
+    let mut preferred = (0..list.len()).filter(meets_threshold).collect::<Vec<_>>();
+    preferred.sort_by_key(|&i| std::cmp::Reverse(list[i].votes));
+
+    let others = (0..list.len()).filter(|i| !meets_threshold(i));
+
+    preferred.into_iter().chain(others).collect()
+}
+
+/// Determine which candidates fill the `seats` obtained by each list, given the preference
+/// `threshold`. A candidate that would be elected on several lists is elected on the list where
+/// they received the most preference votes; on the other lists their seat passes to the next
+/// candidate in line. If a list has too few candidates left, its remaining seats stay vacant.
+pub fn elect(lists: &[Vec<Candidate>], seats: &[Seats], threshold: Fraction) -> Vec<Vec<Elected>> {
+    let rankings = lists
+        .iter()
+        .map(|list| ranking(list, threshold))
+        .collect::<Vec<_>>();
+    let mut passed_over = vec![Vec::<&str>::new(); lists.len()];
+
+    loop {
+        let elected = rankings
+            .iter()
+            .enumerate()
+            .map(|(n, ranking)| {
+                ranking
+                    .iter()
+                    .filter(|&&i| !passed_over[n].contains(&lists[n][i].name.as_str()))
+                    .take(seats[n].count() as usize)
+                    .map(|&position| Elected {
+                        list: n,
+                        position,
+                        preference: frac(lists[n][position].votes.0, 1) >= threshold,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let candidate = |x: &Elected| &lists[x.list][x.position];
+
+        let mut settled = true;
+        for x in elected.iter().flatten() {
+            let best = elected
+                .iter()
+                .flatten()
+                .filter(|y| candidate(y).name == candidate(x).name)
+                .max_by_key(|y| (candidate(y).votes, std::cmp::Reverse(y.list)))
+                .unwrap();
+            if best.list != x.list {
+                passed_over[x.list].push(&candidate(x).name);
+                settled = false;
+            }
+        }
+
+        if settled {
+            return elected;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(candidates: &[(&str, Count)]) -> Vec<Candidate> {
+        candidates
+            .iter()
+            .map(|&(name, votes)| Candidate {
+                name: name.to_string(),
+                votes: Votes(votes),
+            })
+            .collect()
+    }
+
+    fn names(lists: &[Vec<Candidate>], elected: &[Elected]) -> Vec<String> {
+        elected
+            .iter()
+            .map(|x| lists[x.list][x.position].name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn threshold_is_a_quarter_of_the_quota_from_19_seats() {
+        // quota 1000: 25% for 19 seats or more, 50% below
+        assert_eq!(preference_threshold(19000, 19), Some(frac(250, 1)));
+        assert_eq!(preference_threshold(18000, 18), Some(frac(500, 1)));
+        assert_eq!(preference_threshold(1000, 3), Some(frac(1000, 6)));
+        assert_eq!(preference_threshold(1000, 0), None);
+    }
+
+    #[test]
+    fn preference_votes_at_the_threshold_come_first() {
+        let candidates = list(&[("A", 300), ("B", 10), ("C", 250), ("D", 249), ("E", 400)]);
+        // exactly 25% is enough; ties in preference votes keep the order of the list
+        assert_eq!(ranking(&candidates, frac(250, 1)), [4, 0, 2, 1, 3]);
+        // the same votes do not meet a threshold of 50%
+        assert_eq!(ranking(&candidates, frac(500, 1)), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn threshold_depends_on_the_size_of_the_body() {
+        let lists = vec![list(&[("A", 100), ("B", 10), ("C", 300)])];
+
+        // a council of 19 seats with 19000 votes: C has 300 >= 250 votes
+        let threshold = preference_threshold(19000, 19).unwrap();
+        let elected = elect(&lists, &[Seats::filled(1)], threshold);
+        assert_eq!(names(&lists, &elected[0]), ["C"]);
+        assert!(elected[0][0].preference);
+
+        // a council of 18 seats with 18000 votes: C needs 500 votes, so A is elected in list order
+        let threshold = preference_threshold(18000, 18).unwrap();
+        let elected = elect(&lists, &[Seats::filled(1)], threshold);
+        assert_eq!(names(&lists, &elected[0]), ["A"]);
+        assert!(!elected[0][0].preference);
+    }
+
+    #[test]
+    fn seats_are_limited_by_the_candidates_on_a_list() {
+        let lists = vec![
+            list(&[("A", 0), ("B", 0)]),
+            list(&[("C", 0), ("D", 0), ("E", 0)]),
+        ];
+        let mut seats = candidate_limits(&lists);
+        assert_eq!(seats.iter().map(|x| x.limit).collect::<Vec<_>>(), [2, 3]);
+
+        // the first list would receive 4 of the 5 seats, but only has two candidates
+        crate::allocate_per_average(
+            Seats::filled(5),
+            &[Votes(800), Votes(200)],
+            &mut seats,
+            &mut crate::Context::new(&mut LowestListNumber),
+        )
+        .unwrap();
+        assert_eq!(seats.iter().map(|x| x.count()).collect::<Vec<_>>(), [2, 3]);
+
+        let elected = elect(&lists, &seats, frac(1, 1));
+        assert_eq!(names(&lists, &elected[0]), ["A", "B"]);
+        assert_eq!(names(&lists, &elected[1]), ["C", "D", "E"]);
+    }
+
+    #[test]
+    fn candidate_on_several_lists_is_elected_where_they_have_most_votes() {
+        let lists = vec![
+            list(&[("A", 50), ("B", 10), ("C", 5)]),
+            list(&[("A", 80), ("D", 10)]),
+        ];
+        let elected = elect(&lists, &[Seats::filled(2), Seats::filled(1)], frac(1000, 1));
+
+        // A is elected on the second list; their seat on the first list passes to C
+        assert_eq!(names(&lists, &elected[0]), ["B", "C"]);
+        assert_eq!(names(&lists, &elected[1]), ["A"]);
+    }
+
+    #[test]
+    fn seat_stays_vacant_when_a_list_runs_out_of_candidates() {
+        let lists = vec![list(&[("A", 10), ("B", 20)]), list(&[("B", 90)])];
+        let elected = elect(&lists, &[Seats::filled(2), Seats::filled(1)], frac(1000, 1));
+
+        assert_eq!(names(&lists, &elected[0]), ["A"]);
+        assert_eq!(names(&lists, &elected[1]), ["B"]);
+    }
+}
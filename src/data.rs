@@ -1,23 +1,33 @@
+use crate::AllocationError;
 use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 
 pub type Count = u64;
 
 #[derive(Clone, Debug, Copy, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Votes(pub Count);
 
 #[derive(Clone, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "SeatsRepr", into = "SeatsRepr"))]
 pub struct Seats {
     awarded: Count,
     pub limit: Count,
 }
 
 impl Seats {
-    pub fn transfer(&mut self, pool: &mut Seats) {
-        if self.awarded < self.limit {
+    /// Move one seat from the `pool` to these seats; this fails if there is no candidate left to
+    /// occupy it, or if the pool is empty.
+    pub fn transfer(&mut self, pool: &mut Seats) -> Result<(), AllocationError> {
+        if self.awarded < self.limit && pool.awarded > 0 {
             self.awarded += 1;
             pool.awarded -= 1;
+            Ok(())
         } else {
-            panic!("attempt to allocate a seat that will be unoccupied");
+            Err(AllocationError::UnfillableSeats {
+                remaining: pool.awarded,
+            })
         }
     }
 
@@ -45,6 +55,36 @@ impl Seats {
     }
 }
 
+/// Seats are serialized with a missing limit instead of `Count::MAX`, which not every JSON reader
+/// can represent exactly
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SeatsRepr {
+    seats: Count,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<Count>,
+}
+
+#[cfg(feature = "serde")]
+impl From<SeatsRepr> for Seats {
+    fn from(repr: SeatsRepr) -> Seats {
+        Seats {
+            awarded: repr.seats,
+            limit: repr.limit.unwrap_or(Count::MAX),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Seats> for SeatsRepr {
+    fn from(seats: Seats) -> SeatsRepr {
+        SeatsRepr {
+            seats: seats.awarded,
+            limit: (seats.limit != Count::MAX).then_some(seats.limit),
+        }
+    }
+}
+
 impl std::fmt::Display for Seats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self.limit {
@@ -74,23 +114,141 @@ impl PartialEq for Seats {
 
 impl Eq for Seats {}
 
+/// A non-negative rational number. Comparisons are exact for every numerator and denominator;
+/// arithmetic is performed on the reduced result, and `checked_add` and `checked_mul` detect a
+/// result that cannot be represented. The denominator is never zero.
+///
+/// ```
+/// use kiesraad_model::{AllocationError, Count, Fraction, frac};
+///
+/// let max = Count::MAX;
+/// assert!(frac(max, max - 1) < frac(max - 1, max - 2));
+/// assert!(frac(max - 1, max) < frac(max, max));
+/// assert!(frac(1, max) < frac(1, max - 1));
+/// assert_eq!(frac(max, max), frac(1, 1));
+/// assert_eq!(frac(max - 1, 2), frac(max / 2, 1));
+///
+/// assert_eq!(frac(1, max).checked_add(frac(1, max)), Some(frac(2, max)));
+/// assert_eq!(frac(max, 2).checked_mul(frac(2, max)), Some(frac(1, 1)));
+/// assert_eq!(frac(max, 1).checked_add(frac(1, 1)), None);
+/// assert_eq!(frac(1, max).checked_mul(frac(1, 2)), None);
+///
+/// assert_eq!(Fraction::new(3, 4), Ok(frac(3, 4)));
+/// assert_eq!(Fraction::new(3, 0), Err(AllocationError::DivisionByZero));
+/// ```
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "FractionRepr"))]
 pub struct Fraction {
-    pub numerator: Count,
-    pub denominator: Count,
+    pub(crate) numerator: Count,
+    pub(crate) denominator: Count,
 }
 
+/// The fraction `numerator / denominator`, for a denominator that is known not to be zero; use
+/// `Fraction::new` for any other denominator.
+///
+/// # Panics
+///
+/// If the denominator is zero.
 pub fn frac(numerator: Count, denominator: Count) -> Fraction {
-    assert_ne!(denominator, 0);
-    Fraction {
-        numerator,
-        denominator,
+    Fraction::new(numerator, denominator).expect("fraction with denominator zero")
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct FractionRepr {
+    numerator: Count,
+    denominator: Count,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FractionRepr> for Fraction {
+    type Error = AllocationError;
+
+    fn try_from(repr: FractionRepr) -> Result<Fraction, AllocationError> {
+        Fraction::new(repr.numerator, repr.denominator)
+    }
+}
+
+impl Fraction {
+    /// The fraction `numerator / denominator`, or an error if the denominator is zero
+    pub fn new(numerator: Count, denominator: Count) -> Result<Fraction, AllocationError> {
+        if denominator == 0 {
+            return Err(AllocationError::DivisionByZero);
+        }
+        Ok(Fraction {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn numerator(self) -> Count {
+        self.numerator
+    }
+
+    pub fn denominator(self) -> Count {
+        self.denominator
+    }
+
+    /// Bring the fraction into its lowest terms
+    pub fn reduce(self) -> Fraction {
+        let divisor = gcd(self.numerator.into(), self.denominator.into()) as Count;
+        frac(self.numerator / divisor, self.denominator / divisor)
+    }
+
+    /// Add two fractions, or `None` if the result does not fit in a `Fraction`
+    pub fn checked_add(self, other: Fraction) -> Option<Fraction> {
+        let (a, b) = (self.wide(), other.wide());
+        let numerator = (a.0 * b.1).checked_add(b.0 * a.1)?;
+        narrow(numerator, a.1 * b.1)
+    }
+
+    /// Subtract two fractions, or `None` if the result is negative or does not fit in a `Fraction`
+    pub fn checked_sub(self, other: Fraction) -> Option<Fraction> {
+        let (a, b) = (self.wide(), other.wide());
+        let numerator = (a.0 * b.1).checked_sub(b.0 * a.1)?;
+        narrow(numerator, a.1 * b.1)
+    }
+
+    /// Multiply two fractions, or `None` if the result does not fit in a `Fraction`
+    pub fn checked_mul(self, other: Fraction) -> Option<Fraction> {
+        let (a, b) = (self.wide(), other.wide());
+        narrow(a.0 * b.0, a.1 * b.1)
+    }
+
+    /// The numerator and denominator as 128-bit numbers, so that the product of two of them
+    /// cannot overflow
+    fn wide(self) -> (u128, u128) {
+        (self.numerator.into(), self.denominator.into())
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The fraction `numerator / denominator` in its lowest terms, if it fits
+fn narrow(numerator: u128, denominator: u128) -> Option<Fraction> {
+    let divisor = gcd(numerator, denominator);
+    Some(frac(
+        (numerator / divisor).try_into().ok()?,
+        (denominator / divisor).try_into().ok()?,
+    ))
+}
+
+impl From<Count> for Fraction {
+    fn from(count: Count) -> Fraction {
+        frac(count, 1)
     }
 }
 
 impl Ord for Fraction {
     fn cmp(&self, other: &Fraction) -> Ordering {
-        (self.numerator * other.denominator).cmp(&(other.numerator * self.denominator))
+        let (a, b) = (self.wide(), other.wide());
+        (a.0 * b.1).cmp(&(b.0 * a.1))
     }
 }
 
@@ -112,7 +270,8 @@ impl std::fmt::Display for Fraction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         if cfg!(feature = "nice-frac") {
             let whole = self.numerator / self.denominator;
-            let mut frac = self.numerator % self.denominator;
+            let denominator = u128::from(self.denominator);
+            let mut frac = u128::from(self.numerator % self.denominator);
 
             if frac == 0 {
                 write!(f, "{whole}")
@@ -134,8 +293,8 @@ impl std::fmt::Display for Fraction {
                     }
                     seen.push(frac);
                     frac *= 10;
-                    let digit = frac / self.denominator;
-                    frac %= self.denominator;
+                    let digit = frac / denominator;
+                    frac %= denominator;
                     tail.push(b"0123456789"[digit as usize] as char);
                 }
             }
@@ -148,14 +307,101 @@ impl std::fmt::Display for Fraction {
     }
 }
 
-pub fn balloted<T>(vec: Vec<T>) -> Option<T> {
-    use rand::rng;
-    use rand::seq::IteratorRandom;
+/// A procedure for drawing lots when several parties are tied for a seat. The parties are
+/// identified by their index; the winner must be one of the `tied` parties, or `None` if no lot
+/// can be drawn.
+pub trait Ballot {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize>;
+}
+
+impl<B: Ballot + ?Sized> Ballot for Box<B> {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        (**self).draw(tied)
+    }
+}
+
+/// Draw lots using a pseudo-random number generator, which can be seeded to make the outcome
+/// reproducible.
+pub struct RandomBallot(rand::rngs::StdRng);
+
+impl RandomBallot {
+    pub fn seeded(seed: u64) -> Self {
+        use rand::SeedableRng;
+        RandomBallot(rand::rngs::StdRng::seed_from_u64(seed))
+    }
+
+    pub fn from_entropy() -> Self {
+        use rand::SeedableRng;
+        RandomBallot(rand::rngs::StdRng::from_os_rng())
+    }
+}
+
+impl Ballot for RandomBallot {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        use rand::seq::IndexedRandom;
+        tied.choose(&mut self.0).copied()
+    }
+}
+
+/// Replay a pre-recorded schedule of lots, such as an official drawing. Every lot consumes the next
+/// winner in the schedule; if that winner was not part of the tie, no lot can be drawn.
+pub struct ScheduledBallot(std::collections::VecDeque<usize>);
+
+impl ScheduledBallot {
+    pub fn new(winners: impl IntoIterator<Item = usize>) -> Self {
+        ScheduledBallot(winners.into_iter().collect())
+    }
+}
+
+impl Ballot for ScheduledBallot {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        let winner = self.0.pop_front()?;
+        tied.contains(&winner).then_some(winner)
+    }
+}
+
+/// Deterministically award a tied seat to the party with the lowest list number.
+pub struct LowestListNumber;
 
-    #[cfg(feature = "chatty")]
-    if vec.len() > 1 {
-        eprintln!("non-deterministic choice!");
+impl Ballot for LowestListNumber {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        tied.iter().min().copied()
     }
+}
 
-    vec.into_iter().choose(&mut rng())
+/// Keep a record of all the lots drawn by another ballot, in the order that they were drawn.
+pub struct RecordedBallot<B> {
+    pub ballot: B,
+    pub draws: Vec<(Vec<usize>, usize)>,
+}
+
+impl<B: Ballot> RecordedBallot<B> {
+    pub fn new(ballot: B) -> Self {
+        RecordedBallot {
+            ballot,
+            draws: Vec::new(),
+        }
+    }
+}
+
+impl<B: Ballot> Ballot for RecordedBallot<B> {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        let winner = self.ballot.draw(tied)?;
+        self.draws.push((tied.to_vec(), winner));
+        Some(winner)
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_fraction() {
+        let fraction = serde_json::from_str::<Fraction>(r#"{"numerator": 3, "denominator": 4}"#);
+        assert_eq!(fraction.unwrap(), frac(3, 4));
+
+        let zero = serde_json::from_str::<Fraction>(r#"{"numerator": 3, "denominator": 0}"#);
+        assert_eq!(zero.unwrap_err().to_string(), "division by zero");
+    }
 }
@@ -0,0 +1,135 @@
+use crate::data::*;
+use crate::election::*;
+use crate::trace::*;
+use crate::{AllocationError, Context, Rules};
+
+/// An election in which two sets of rules lead to a different apportionment of the seats
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The seats of every party under either set of rules, or why they could not be apportioned
+    /// (for instance because seats remain unoccupied)
+    pub seats: [Result<Vec<Seats>, AllocationError>; 2],
+    /// The steps taken under either set of rules, not including the standings
+    pub traces: [Vec<Event>; 2],
+    /// The number of steps that both traces have in common
+    pub split: usize,
+}
+
+/// Perform the apportionment of an `election` under both sets of `rules`, and describe how they
+/// differ if the seats are not the same. Lots are always won by the lowest list number, so that
+/// differences are not caused by the drawing of lots.
+pub fn find_divergence(election: &Election, rules: [Rules; 2]) -> Option<Divergence> {
+    let run = |rules: Rules| {
+        let mut trace = Vec::new();
+        let seats = election
+            .allocate(&mut Context::observed(&mut LowestListNumber, &mut trace).with_rules(rules));
+        trace.retain(|event| !matches!(event, Event::Standings(_)));
+        (seats, trace)
+    };
+    let (first, first_trace) = run(rules[0]);
+    let (second, second_trace) = run(rules[1]);
+
+    fn counts(seats: &Result<Vec<Seats>, AllocationError>) -> Result<Vec<Count>, &AllocationError> {
+        seats
+            .as_ref()
+            .map(|seats| seats.iter().map(|x| x.count()).collect())
+    }
+    if counts(&first) == counts(&second) {
+        return None;
+    }
+
+    let split = std::iter::zip(&first_trace, &second_trace)
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    Some(Divergence {
+        seats: [first, second],
+        traces: [first_trace, second_trace],
+        split,
+    })
+}
+
+/// Reduce an election in which the `rules` diverge to a smaller one in which they still do in the
+/// same way (i.e. the traces split at the same kind of step): with fewer parties, seats, votes and
+/// candidates.
+pub fn minimize(election: &Election, rules: [Rules; 2]) -> Election {
+    let split = |divergence: Divergence| {
+        divergence
+            .traces
+            .map(|trace| trace.get(divergence.split).map(std::mem::discriminant))
+    };
+    let Some(original) = find_divergence(election, rules).map(split) else {
+        return election.clone();
+    };
+    let diverges =
+        |election: &Election| find_divergence(election, rules).map(split) == Some(original);
+
+    let mut current = election.clone();
+    loop {
+        let Some(smaller) = simplifications(&current).find(diverges) else {
+            return current;
+        };
+        current = smaller;
+    }
+}
+
+/// All the elections that are a single step simpler than the given one
+fn simplifications(election: &Election) -> impl Iterator<Item = Election> + '_ {
+    let parties = election.parties.len();
+
+    let without_party = (0..parties)
+        .filter(move |_| parties > 1)
+        .map(move |i| remove_party(election, i));
+
+    let fewer_seats = (election.seats > 1).then(|| Election {
+        seats: election.seats - 1,
+        ..election.clone()
+    });
+
+    let fewer_votes = (0..parties).flat_map(move |i| {
+        let Votes(votes) = election.parties[i].votes;
+        // try to take away large amounts of votes first, but leave at least one
+        std::iter::successors(Some(votes / 2), |&step| (step > 1).then_some(step / 2))
+            .filter(|&step| step > 0)
+            .map(move |step| {
+                let mut smaller = election.clone();
+                smaller.parties[i].votes = Votes(votes - step);
+                smaller
+            })
+    });
+
+    let fewer_candidates = (0..parties).filter_map(move |i| {
+        let limit = election.parties[i].candidates?.checked_sub(1)?;
+        let mut smaller = election.clone();
+        smaller.parties[i].candidates = Some(limit);
+        Some(smaller)
+    });
+
+    let unlimited = (0..parties)
+        .filter(move |&i| election.parties[i].candidates.is_some())
+        .map(move |i| {
+            let mut simpler = election.clone();
+            simpler.parties[i].candidates = None;
+            simpler
+        });
+
+    without_party
+        .chain(fewer_seats)
+        .chain(unlimited)
+        .chain(fewer_votes)
+        .chain(fewer_candidates)
+}
+
+/// The election without party `i`
+fn remove_party(election: &Election, i: usize) -> Election {
+    let mut smaller = election.clone();
+    smaller.parties.remove(i);
+    for members in &mut smaller.combinations {
+        members.retain(|&j| j != i);
+        for j in members.iter_mut().filter(|j| **j > i) {
+            *j -= 1;
+        }
+    }
+    smaller.combinations.retain(|members| !members.is_empty());
+    smaller
+}
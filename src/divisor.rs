@@ -0,0 +1,197 @@
+use crate::data::*;
+use std::cmp::Ordering;
+
+/// The highest averages methods: a party that has `s` seats claims its next seat with its votes
+/// divided by the signpost `d(s)`, and every seat goes to the party with the highest claim.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DivisorMethod {
+    /// `d(s) = s + 1`
+    DHondt,
+    /// `d(s) = s + 1/2`
+    SainteLague,
+    /// As Sainte-Laguë, but with a first signpost of 0.7 (i.e. a first divisor of 1.4)
+    ModifiedSainteLague,
+    /// `d(s) = s + 2`
+    Imperiali,
+    /// `d(s) = s`, so that every party receives a seat before any party receives a second
+    Adams,
+    /// `d(s) = s + 1/3`
+    Danish,
+    /// `d(s)` is the geometric mean of `s` and `s + 1`
+    HuntingtonHill,
+}
+
+/// A signpost of a divisor method
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signpost {
+    Rational(Fraction),
+    /// The square root of a natural number
+    SquareRoot(Count),
+}
+
+impl DivisorMethod {
+    /// The signpost for a party that has `seats` seats
+    pub fn signpost(self, seats: Count) -> Signpost {
+        match self {
+            DivisorMethod::DHondt => Signpost::Rational(frac(seats + 1, 1)),
+            DivisorMethod::SainteLague => Signpost::Rational(frac(2 * seats + 1, 2)),
+            DivisorMethod::ModifiedSainteLague if seats == 0 => Signpost::Rational(frac(7, 10)),
+            DivisorMethod::ModifiedSainteLague => Signpost::Rational(frac(2 * seats + 1, 2)),
+            DivisorMethod::Imperiali => Signpost::Rational(frac(seats + 2, 1)),
+            DivisorMethod::Adams => Signpost::Rational(frac(seats, 1)),
+            DivisorMethod::Danish => Signpost::Rational(frac(3 * seats + 1, 3)),
+            DivisorMethod::HuntingtonHill => Signpost::SquareRoot(seats * (seats + 1)),
+        }
+    }
+
+    /// The claim of a party to its next seat
+    pub fn quotient(self, votes: Votes, seats: Seats) -> Quotient {
+        Quotient {
+            votes,
+            signpost: self.signpost(seats.count()),
+        }
+    }
+}
+
+impl std::fmt::Display for DivisorMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            DivisorMethod::DHondt => write!(f, "D'Hondt"),
+            DivisorMethod::SainteLague => write!(f, "Sainte-Laguë"),
+            DivisorMethod::ModifiedSainteLague => write!(f, "modified Sainte-Laguë"),
+            DivisorMethod::Imperiali => write!(f, "Imperiali"),
+            DivisorMethod::Adams => write!(f, "Adams"),
+            DivisorMethod::Danish => write!(f, "Danish"),
+            DivisorMethod::HuntingtonHill => write!(f, "Huntington-Hill"),
+        }
+    }
+}
+
+/// The votes of a party divided by a signpost. This is compared exactly, also when the signpost is
+/// irrational; a quotient with a signpost of zero is infinite, and ranks above all finite quotients
+/// (and by number of votes among the infinite ones). Quotients are only meant to be compared with
+/// quotients of the same divisor method.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let quotient = |method: DivisorMethod, votes, seats| {
+///     let mut claim = Seats::unlimited();
+///     for _ in 0..seats {
+///         claim.transfer(&mut Seats::filled(1)).unwrap();
+///     }
+///     method.quotient(Votes(votes), claim)
+/// };
+///
+/// // 10/√2 > 14/√6 > 7/√2
+/// let hill = DivisorMethod::HuntingtonHill;
+/// assert!(quotient(hill, 10, 1) > quotient(hill, 14, 2));
+/// assert!(quotient(hill, 14, 2) > quotient(hill, 7, 1));
+/// assert!(quotient(hill, 1, 0) > quotient(hill, Count::MAX, 1));
+///
+/// let sainte_lague = DivisorMethod::SainteLague;
+/// assert!(quotient(sainte_lague, Count::MAX, 1) > quotient(sainte_lague, Count::MAX - 1, 1));
+/// assert_eq!(quotient(sainte_lague, 3, 1), quotient(sainte_lague, 1, 0));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Quotient {
+    votes: Votes,
+    signpost: Signpost,
+}
+
+impl Quotient {
+    /// The quotient as a ratio of two numbers: the quotient itself for a rational signpost, and its
+    /// square for an irrational one; the denominator is zero for an infinite quotient.
+    fn ratio(&self) -> (bool, u128, u128) {
+        let Votes(votes) = self.votes;
+        match self.signpost {
+            Signpost::Rational(signpost) => (
+                false,
+                u128::from(votes) * u128::from(signpost.denominator),
+                signpost.numerator.into(),
+            ),
+            Signpost::SquareRoot(square) => (true, u128::from(votes).pow(2), square.into()),
+        }
+    }
+}
+
+/// Compare `a / b` with `c / d` without overflow, by comparing their continued fractions
+fn compare_ratios(mut a: u128, mut b: u128, mut c: u128, mut d: u128) -> Ordering {
+    loop {
+        match (a / b).cmp(&(c / d)) {
+            Ordering::Equal => {}
+            unequal => return unequal,
+        }
+        (a, c) = (a % b, c % d);
+        match (a, c) {
+            (0, 0) => return Ordering::Equal,
+            (0, _) => return Ordering::Less,
+            (_, 0) => return Ordering::Greater,
+            // a/b < c/d if and only if b/a > d/c
+            _ => (a, b, c, d) = (d, c, b, a),
+        }
+    }
+}
+
+impl Ord for Quotient {
+    fn cmp(&self, other: &Quotient) -> Ordering {
+        let (squared, a, b) = self.ratio();
+        let (other_squared, c, d) = other.ratio();
+        match (b, d) {
+            (0, 0) => self.votes.cmp(&other.votes),
+            (0, _) => Ordering::Greater,
+            (_, 0) => Ordering::Less,
+            _ => squared
+                .cmp(&other_squared)
+                .then_with(|| compare_ratios(a, b, c, d)),
+        }
+    }
+}
+
+impl PartialOrd for Quotient {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Quotient {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.cmp(other), Ordering::Equal)
+    }
+}
+
+impl Eq for Quotient {}
+
+/// The quotient as a fraction, as it appears in a trace. An irrational quotient is rounded down to
+/// three decimals, and an infinite quotient is represented by the number of votes.
+impl From<Quotient> for Fraction {
+    fn from(quotient: Quotient) -> Fraction {
+        let Votes(votes) = quotient.votes;
+        let (numerator, denominator) = match quotient.signpost {
+            Signpost::Rational(signpost) if signpost.numerator > 0 => (
+                u128::from(votes) * u128::from(signpost.denominator),
+                u128::from(signpost.numerator),
+            ),
+            Signpost::SquareRoot(square) if square > 0 => {
+                let votes = u128::from(votes);
+                match (votes * 1000).checked_pow(2) {
+                    Some(scaled) => ((scaled / u128::from(square)).isqrt(), 1000),
+                    None => ((votes.pow(2) / u128::from(square)).isqrt(), 1),
+                }
+            }
+            _ => (votes.into(), 1),
+        };
+        approximate(numerator, denominator)
+    }
+}
+
+/// The fraction `numerator / denominator`, with as much precision as fits
+fn approximate(mut numerator: u128, mut denominator: u128) -> Fraction {
+    while numerator > Count::MAX.into() || denominator > Count::MAX.into() {
+        numerator /= 2;
+        denominator = (denominator / 2).max(1);
+    }
+    frac(numerator as Count, denominator as Count)
+}
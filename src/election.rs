@@ -0,0 +1,328 @@
+use crate::data::*;
+use crate::trace::*;
+use crate::{
+    AllocationError, Ballot, Context, DivisorMethod, QuotaMethod, Rules, Threshold, allocate_1918,
+    allocate_1922, allocate_bongaerts, allocate_combined, allocate_combined_before_2017,
+    allocate_national, allocate_per_average, allocate_per_divisor, allocate_per_quota,
+    allocate_per_surplus, allocate_per_surplus_before_2017,
+};
+
+/// The methods by which the seats of a body can be apportioned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Method {
+    /// Largest averages (see `allocate_per_average`)
+    Averages,
+    /// Largest surpluses (see `allocate_per_surplus`)
+    Surpluses,
+    /// Largest surpluses, as until 2017 (see `allocate_per_surplus_before_2017`)
+    SurplusesBefore2017,
+    /// Largest averages or surpluses with list combinations, as in councils until 2017 (see
+    /// `allocate_combined_before_2017`)
+    CombinedBefore2017,
+    /// Largest averages with a voting threshold of one whole seat (see `allocate_national`)
+    National,
+    /// As `National`, with list combinations (see `allocate_combined`)
+    Combined,
+    /// The method used from 1925 until 1933 (see `allocate_bongaerts`)
+    Bongaerts,
+    /// The method used in 1918 (see `allocate_1918`)
+    Archaic1918,
+    /// The method used in 1922 (see `allocate_1922`)
+    Archaic1922,
+    /// Highest averages by any divisor method (see `allocate_per_divisor`)
+    Divisor(DivisorMethod),
+    /// Largest surpluses by any quota method (see `allocate_per_quota`)
+    Quota(QuotaMethod),
+}
+
+impl Method {
+    /// The method that Dutch law prescribes for a body with the given number of seats, other than
+    /// the Tweede Kamer and the European Parliament
+    pub fn for_seats(seats: Count) -> Method {
+        if seats >= 19 {
+            Method::Averages
+        } else {
+            Method::Surpluses
+        }
+    }
+
+    /// Perform a seat apportionment using this method. The `combinations` are only used by
+    /// `Method::Combined` and `Method::CombinedBefore2017`.
+    pub fn allocate(
+        self,
+        total_seats: Seats,
+        votes: &[Votes],
+        seats: &mut [Seats],
+        combinations: &[Vec<usize>],
+        ctx: &mut Context,
+    ) -> Result<(), AllocationError> {
+        match self {
+            Method::Averages => allocate_per_average(total_seats, votes, seats, ctx),
+            Method::Surpluses => allocate_per_surplus(total_seats, votes, seats, ctx),
+            Method::SurplusesBefore2017 => {
+                allocate_per_surplus_before_2017(total_seats, votes, seats, ctx)
+            }
+            Method::CombinedBefore2017 => {
+                allocate_combined_before_2017(total_seats, votes, seats, combinations, ctx)
+            }
+            Method::National => allocate_national(total_seats, votes, seats, ctx),
+            Method::Combined => allocate_combined(total_seats, votes, seats, combinations, ctx),
+            Method::Bongaerts => allocate_bongaerts(total_seats, votes, seats, ctx),
+            Method::Archaic1918 => allocate_1918(total_seats, votes, seats, ctx),
+            Method::Archaic1922 => allocate_1922(total_seats, votes, seats, ctx),
+            Method::Divisor(method) => allocate_per_divisor(method, total_seats, votes, seats, ctx),
+            Method::Quota(method) => allocate_per_quota(method, total_seats, votes, seats, ctx),
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Method::Averages => write!(f, "largest averages"),
+            Method::Surpluses => write!(f, "largest surpluses"),
+            Method::SurplusesBefore2017 => write!(f, "largest surpluses, as until 2017"),
+            Method::CombinedBefore2017 => {
+                write!(
+                    f,
+                    "largest averages or surpluses with list combinations, as until 2017"
+                )
+            }
+            Method::National => {
+                write!(
+                    f,
+                    "largest averages (with voting threshold of one whole seat)"
+                )
+            }
+            Method::Combined => write!(f, "largest averages with list combinations"),
+            Method::Bongaerts => write!(f, "largest surpluses, as in 1925-1933"),
+            Method::Archaic1918 => write!(f, "largest surpluses, as in 1918"),
+            Method::Archaic1922 => write!(f, "largest surpluses, as in 1922"),
+            Method::Divisor(method) => write!(f, "highest averages ({method})"),
+            Method::Quota(method) => write!(f, "{method}"),
+        }
+    }
+}
+
+/// A party that takes part in an election
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Party {
+    pub name: String,
+    /// The list number of the party, if it is not its position among the parties
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub list: Option<usize>,
+    pub votes: Votes,
+    /// The number of candidates on the list of the party, if it is limited
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub candidates: Option<Count>,
+}
+
+/// How a party is referred to: its list number, followed by its name if it has one
+fn label(list: usize, name: &str) -> String {
+    match name {
+        "" => list.to_string(),
+        name => format!("{list} ({name})"),
+    }
+}
+
+/// A description of an election: the body that is elected, the number of seats, how they are
+/// apportioned, and the parties that take part in it, in order of their list number.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let party = |name: &str, votes| Party {
+///     name: name.to_string(),
+///     list: None,
+///     votes: Votes(votes),
+///     candidates: None,
+/// };
+/// let election = Election {
+///     body: Some("Gemeenteraad".to_string()),
+///     seats: 9,
+///     method: Method::for_seats(9),
+///     parties: vec![party("Lokaal", 600), party("Samen", 300), party("Anders", 100)],
+///     combinations: Vec::new(),
+///     rules: Rules::default(),
+///     threshold: None,
+/// };
+///
+/// let outcome = election.run(&mut LowestListNumber).unwrap();
+/// let seats = outcome.parties.iter().map(|x| (x.name.as_str(), x.seats.count()));
+/// assert_eq!(seats.collect::<Vec<_>>(), [("Lokaal", 5), ("Samen", 3), ("Anders", 1)]);
+/// assert_eq!(outcome.steps()[1], "whole seats for list 1 (Lokaal): 5");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Election {
+    /// The name of the body that is elected, or of the region that elects it
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub body: Option<String>,
+    pub seats: Count,
+    pub method: Method,
+    pub parties: Vec<Party>,
+    /// The indices of the parties that formed list combinations
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub combinations: Vec<Vec<usize>>,
+    /// The reading of the Kieswet that is followed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Rules,
+    /// An electoral threshold for all parties, in addition to the one of the method
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub threshold: Option<Threshold>,
+}
+
+impl Election {
+    /// The list number of every party
+    pub fn list_numbers(&self) -> Vec<usize> {
+        self.parties
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x.list.unwrap_or(i + 1))
+            .collect()
+    }
+
+    /// How every party is referred to in a description of the apportionment (see
+    /// `Event::describe`)
+    pub fn labels(&self) -> Vec<String> {
+        std::iter::zip(self.list_numbers(), &self.parties)
+            .map(|(list, party)| label(list, &party.name))
+            .collect()
+    }
+
+    /// The votes of every party
+    pub fn votes(&self) -> Vec<Votes> {
+        self.parties.iter().map(|x| x.votes).collect()
+    }
+
+    /// The seats of every party before the apportionment, limited by its number of candidates
+    pub fn candidate_limits(&self) -> Vec<Seats> {
+        self.parties
+            .iter()
+            .map(|x| x.candidates.map_or(Seats::unlimited(), Seats::limited))
+            .collect()
+    }
+
+    /// Perform the seat apportionment for this election, following the rules and threshold of the
+    /// `ctx` rather than those of the election
+    pub fn allocate(&self, ctx: &mut Context) -> Result<Vec<Seats>, AllocationError> {
+        let mut seats = self.candidate_limits();
+        self.method.allocate(
+            Seats::filled(self.seats),
+            &self.votes(),
+            &mut seats,
+            &self.combinations,
+            ctx,
+        )?;
+        Ok(seats)
+    }
+
+    /// Perform the seat apportionment for this election, drawing lots using the `ballot`
+    pub fn run(&self, ballot: &mut dyn Ballot) -> Result<Outcome, AllocationError> {
+        let mut trace = Vec::new();
+        let seats = self.allocate(&mut Context {
+            threshold: self.threshold,
+            ..Context::observed(ballot, &mut trace).with_rules(self.rules)
+        })?;
+        Ok(Outcome::new(self, &seats, &trace))
+    }
+}
+
+/// The seats obtained by a party
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartySeats {
+    /// The list number of the party, starting from one
+    pub list: usize,
+    pub name: String,
+    pub votes: Votes,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub seats: Seats,
+}
+
+/// A lot that was drawn between parties, identified by their list number
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lot {
+    pub tied: Vec<usize>,
+    pub winner: usize,
+}
+
+/// A description of the outcome of an election
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    pub method: Method,
+    pub parties: Vec<PartySeats>,
+    pub lots: Vec<Lot>,
+    /// The steps taken in the apportionment, in which parties are identified by their index
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub trace: Vec<Event>,
+}
+
+impl Outcome {
+    /// Describe the `seats` that resulted from an `election`, with the steps in its `trace`
+    pub fn new(election: &Election, seats: &[Seats], trace: &[Event]) -> Outcome {
+        let lists = election.list_numbers();
+        Outcome {
+            method: election.method,
+            parties: std::iter::zip(&election.parties, seats)
+                .zip(&lists)
+                .map(|((party, &seats), &list)| PartySeats {
+                    list,
+                    name: party.name.clone(),
+                    votes: party.votes,
+                    seats,
+                })
+                .collect(),
+            lots: trace
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Lot { tied, winner } => Some(Lot {
+                        tied: tied.iter().map(|&i| lists[i]).collect(),
+                        winner: lists[*winner],
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            trace: trace.to_vec(),
+        }
+    }
+
+    /// How every party is referred to in a description of the apportionment
+    pub fn labels(&self) -> Vec<String> {
+        self.parties
+            .iter()
+            .map(|x| label(x.list, &x.name))
+            .collect()
+    }
+
+    /// The steps taken in the apportionment, referring to parties by list number and name
+    pub fn steps(&self) -> Vec<String> {
+        let labels = self.labels();
+        self.trace.iter().map(|x| x.describe(&labels)).collect()
+    }
+
+    /// The seats of every party
+    pub fn seats(&self) -> Vec<Seats> {
+        self.parties.iter().map(|x| x.seats).collect()
+    }
+}
@@ -0,0 +1,768 @@
+use crate::candidates::*;
+use crate::data::*;
+use crate::registry::ElectionCode;
+
+use quick_xml::events::Event as XmlEvent;
+
+/// Something that went wrong while reading an EML file
+#[derive(Debug)]
+pub enum EmlError {
+    /// The file is not well-formed XML
+    Xml(quick_xml::Error),
+    /// The file is not the expected kind of EML document
+    Kind(String),
+    /// A required element is missing
+    Missing(&'static str),
+    /// A number could not be read
+    Number(String),
+    /// A list or candidate is not part of the election
+    Unknown(String),
+}
+
+impl std::fmt::Display for EmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            EmlError::Xml(err) => write!(f, "invalid XML: {err}"),
+            EmlError::Kind(id) => write!(f, "unexpected kind of EML document: {id}"),
+            EmlError::Missing(name) => write!(f, "missing element: {name}"),
+            EmlError::Number(text) => write!(f, "not a number: {text}"),
+            EmlError::Unknown(what) => write!(f, "unknown {what}"),
+        }
+    }
+}
+
+impl std::error::Error for EmlError {}
+
+impl From<quick_xml::Error> for EmlError {
+    fn from(err: quick_xml::Error) -> Self {
+        EmlError::Xml(err)
+    }
+}
+
+impl From<quick_xml::encoding::EncodingError> for EmlError {
+    fn from(err: quick_xml::encoding::EncodingError) -> Self {
+        EmlError::Xml(err.into())
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for EmlError {
+    fn from(err: quick_xml::events::attributes::AttrError) -> Self {
+        EmlError::Xml(err.into())
+    }
+}
+
+/// An XML element, with namespace prefixes removed from its name and attributes
+#[derive(Debug, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn parse(xml: &str) -> Result<Element, EmlError> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut stack = vec![Element::default()];
+
+        let open = |start: &quick_xml::events::BytesStart| -> Result<Element, EmlError> {
+            let mut attributes = Vec::new();
+            for attr in start.attributes() {
+                let attr = attr?;
+                attributes.push((
+                    String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned(),
+                    attr.unescape_value()?.into_owned(),
+                ));
+            }
+            Ok(Element {
+                name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+                attributes,
+                ..Element::default()
+            })
+        };
+
+        loop {
+            match reader.read_event()? {
+                XmlEvent::Start(start) => stack.push(open(&start)?),
+                XmlEvent::Empty(start) => {
+                    let element = open(&start)?;
+                    stack.last_mut().unwrap().children.push(element);
+                }
+                XmlEvent::End(_) => {
+                    let element = stack.pop().unwrap();
+                    stack.last_mut().unwrap().children.push(element);
+                }
+                XmlEvent::Text(text) => stack.last_mut().unwrap().text += &text.xml_content()?,
+                XmlEvent::CData(text) => stack.last_mut().unwrap().text += &text.xml_content()?,
+                XmlEvent::GeneralRef(entity) => {
+                    let resolved = match entity.resolve_char_ref()? {
+                        Some(ch) => ch.to_string(),
+                        None => quick_xml::escape::resolve_predefined_entity(&entity.decode()?)
+                            .unwrap_or_default()
+                            .to_string(),
+                    };
+                    stack.last_mut().unwrap().text += &resolved;
+                }
+                XmlEvent::Eof => break,
+                _ => {}
+            }
+        }
+
+        let mut document = stack.pop().unwrap();
+        document.children.pop().ok_or(EmlError::Missing("EML"))
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|x| x.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |x| x.name == name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Follow a path of child elements
+    pub fn find(&self, path: &[&'static str]) -> Result<&Element, EmlError> {
+        path.iter().try_fold(self, |element, name| {
+            element.child(name).ok_or(EmlError::Missing(name))
+        })
+    }
+
+    pub fn count(&self) -> Result<Count, EmlError> {
+        self.text
+            .trim()
+            .parse()
+            .map_err(|_| EmlError::Number(self.text.clone()))
+    }
+}
+
+/// The identification of an election in an EML document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElectionId {
+    /// The identifier used by the Kiesraad, e.g. "GR2022_Amsterdam" or "TK2023"
+    pub id: String,
+    pub name: String,
+    /// The kind of body that is elected, e.g. "GR" for a municipal council
+    pub category: String,
+    /// The more specific kind of body, e.g. "GR1" for a council of less than 19 seats
+    pub subcategory: Option<String>,
+    /// The region for which the body is elected, if it is not the whole country
+    pub domain: Option<String>,
+    /// The code of the region, e.g. the CBS code of a municipality
+    pub domain_id: Option<String>,
+    /// The date of the election, as it appears in the document
+    pub date: Option<String>,
+}
+
+impl ElectionId {
+    /// The identifier of the election as an `ElectionCode`, with the date of the election if the
+    /// identifier only contains its year
+    ///
+    /// ```
+    /// use kiesraad_model::*;
+    ///
+    /// let id = ElectionId {
+    ///     id: "GR2026_Roermond".to_string(),
+    ///     name: "Gemeenteraad Roermond 2026".to_string(),
+    ///     category: "GR".to_string(),
+    ///     subcategory: None,
+    ///     domain: Some("Roermond".to_string()),
+    ///     domain_id: None,
+    ///     date: Some("2026-03-18".to_string()),
+    /// };
+    /// assert_eq!(id.code().unwrap().to_string(), "GR20260318_Roermond");
+    /// ```
+    pub fn code(&self) -> Result<ElectionCode, String> {
+        let mut code = self.id.parse::<ElectionCode>()?;
+        if code.date.is_none()
+            && let Some(date) = &self.date
+            && date.get(..4) == Some(&code.year.to_string())
+        {
+            let number = |range: std::ops::Range<usize>| date.get(range)?.parse::<u8>().ok();
+            code.date = number(5..7).zip(number(8..10));
+        }
+        Ok(code)
+    }
+
+    fn read(election: &Element) -> Result<ElectionId, EmlError> {
+        let identifier = election.find(&["ElectionIdentifier"])?;
+        Ok(ElectionId {
+            id: identifier.attribute("Id").unwrap_or_default().to_string(),
+            name: identifier.find(&["ElectionName"])?.text.trim().to_string(),
+            category: identifier
+                .find(&["ElectionCategory"])?
+                .text
+                .trim()
+                .to_string(),
+            subcategory: identifier
+                .child("ElectionSubcategory")
+                .map(|x| x.text.trim().to_string()),
+            domain: identifier
+                .child("ElectionDomain")
+                .map(|x| x.text.trim().to_string()),
+            domain_id: identifier
+                .child("ElectionDomain")
+                .and_then(|x| x.attribute("Id"))
+                .map(str::to_string),
+            date: identifier
+                .child("ElectionDate")
+                .map(|x| x.text.trim().to_string()),
+        })
+    }
+}
+
+/// The metadata of an EML document, which identify the document rather than the election
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EmlHeader {
+    pub transaction_id: Option<String>,
+    /// The date on which the document was issued, as it appears in the document
+    pub issue_date: Option<String>,
+    /// The time at which the document was created (`kr:CreationDateTime`)
+    pub creation_date_time: Option<String>,
+}
+
+impl EmlHeader {
+    fn read(document: &Element) -> EmlHeader {
+        let text = |name| document.child(name).map(|x| x.text.trim().to_string());
+        EmlHeader {
+            transaction_id: text("TransactionId"),
+            issue_date: text("IssueDate"),
+            creation_date_time: text("CreationDateTime"),
+        }
+    }
+}
+
+/// The votes for a single list in an EML 510 file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListTotals {
+    /// The identifier of the list, which is its list number
+    pub id: String,
+    pub name: String,
+    pub votes: Votes,
+    /// The preference votes of the candidates on the list. EML 510 does not contain the names of
+    /// candidates; they are identified by their short code if it is present, and otherwise by
+    /// the list number and their position on the list (e.g. "3.12").
+    pub candidates: Vec<Candidate>,
+    /// The identifiers of the candidates, in the same order, which is their position on the list
+    pub candidate_ids: Vec<String>,
+}
+
+/// The total count of a contest, as published in an EML 510 file ("totaaltelling")
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Totals {
+    pub header: EmlHeader,
+    pub election: ElectionId,
+    /// The identifier of the contest; for most elections there is only one
+    pub contest: String,
+    pub lists: Vec<ListTotals>,
+    pub blank: Votes,
+    pub invalid: Votes,
+}
+
+impl Totals {
+    /// The votes per list
+    pub fn votes(&self) -> Vec<Votes> {
+        self.lists.iter().map(|x| x.votes).collect()
+    }
+
+    /// The number of valid votes, which does not include blank votes
+    pub fn valid(&self) -> Votes {
+        Votes(self.lists.iter().map(|x| x.votes.0).sum())
+    }
+
+    /// The candidates on every list
+    pub fn candidates(&self) -> Vec<Vec<Candidate>> {
+        self.lists.iter().map(|x| x.candidates.clone()).collect()
+    }
+
+    /// The candidates that fill the `seats` of every list, based on their preference votes (see
+    /// `elect`)
+    pub fn elected(&self, seats: &[Seats]) -> Vec<Vec<Elected>> {
+        let seat_count = seats.iter().map(|x| x.count()).sum();
+        match preference_threshold(self.valid().0, seat_count) {
+            Some(threshold) => elect(&self.candidates(), seats, threshold),
+            None => vec![Vec::new(); seats.len()],
+        }
+    }
+
+    /// The seats per list, limited by the number of candidates on each list
+    pub fn candidate_limits(&self) -> Vec<Seats> {
+        self.lists
+            .iter()
+            .map(|x| Seats::limited(x.candidates.len() as Count))
+            .collect()
+    }
+}
+
+fn eml_kind<'a>(document: &'a Element, expected: &str) -> Result<&'a Element, EmlError> {
+    let id = document.attribute("Id").unwrap_or_default();
+    if document.name != "EML" || id != expected {
+        return Err(EmlError::Kind(format!("{} {id}", document.name)));
+    }
+    Ok(document)
+}
+
+/// Read the total counts of every contest in an EML 510 file
+pub fn read_eml_510(xml: &str) -> Result<Vec<Totals>, EmlError> {
+    let document = Element::parse(xml)?;
+    let election = eml_kind(&document, "510")?.find(&["Count", "Election"])?;
+    let id = ElectionId::read(election)?;
+    let header = EmlHeader::read(&document);
+
+    election
+        .find(&["Contests"])?
+        .children("Contest")
+        .map(|contest| {
+            let total = contest.find(&["TotalVotes"])?;
+            let mut lists = Vec::<ListTotals>::new();
+            for selection in total.children("Selection") {
+                let votes = Votes(selection.find(&["ValidVotes"])?.count()?);
+                if let Some(affiliation) = selection.child("AffiliationIdentifier") {
+                    lists.push(ListTotals {
+                        id: affiliation.attribute("Id").unwrap_or_default().to_string(),
+                        name: affiliation
+                            .child("RegisteredName")
+                            .map(|x| x.text.trim().to_string())
+                            .unwrap_or_default(),
+                        votes,
+                        candidates: Vec::new(),
+                        candidate_ids: Vec::new(),
+                    });
+                } else {
+                    let candidate = selection.find(&["Candidate", "CandidateIdentifier"])?;
+                    let list = lists
+                        .last_mut()
+                        .ok_or(EmlError::Missing("AffiliationIdentifier"))?;
+                    let id = candidate.attribute("Id").unwrap_or_default();
+                    let name = match candidate.attribute("ShortCode") {
+                        Some(code) => code.to_string(),
+                        None => format!("{}.{id}", list.id),
+                    };
+                    list.candidates.push(Candidate { name, votes });
+                    list.candidate_ids.push(id.to_string());
+                }
+            }
+
+            let rejected = |reason: &str| -> Result<Votes, EmlError> {
+                Ok(Votes(
+                    total
+                        .children("RejectedVotes")
+                        .filter(|x| x.attribute("ReasonCode") == Some(reason))
+                        .map(|x| x.count())
+                        .sum::<Result<Count, _>>()?,
+                ))
+            };
+
+            Ok(Totals {
+                header: header.clone(),
+                election: id.clone(),
+                contest: contest
+                    .find(&["ContestIdentifier"])?
+                    .attribute("Id")
+                    .unwrap_or_default()
+                    .to_string(),
+                lists,
+                blank: rejected("blanco")?,
+                invalid: rejected("ongeldig")?,
+            })
+        })
+        .collect()
+}
+
+/// The seats awarded in a contest, as published in an EML 520 file ("resultaat")
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContestSeats {
+    pub header: EmlHeader,
+    pub election: ElectionId,
+    pub contest: String,
+    /// The identifier of every list, together with the number of its candidates that were elected
+    pub seats: Vec<(String, Count)>,
+    /// The identifiers of the elected candidates of every list, in the order of their ranking
+    pub elected: Vec<(String, Vec<String>)>,
+}
+
+impl ContestSeats {
+    /// The elected candidates, identified by their position on the lists in the `totals` of the
+    /// same contest. Whether a candidate was elected by preference votes is not part of EML 520;
+    /// it is taken to be the case if the candidate meets the preference threshold.
+    pub fn elected_in(&self, totals: &Totals) -> Result<Vec<Vec<Elected>>, EmlError> {
+        let seat_count = self.seats.iter().map(|(_, seats)| seats).sum();
+        let threshold = preference_threshold(totals.valid().0, seat_count);
+        let mut elected = vec![Vec::new(); totals.lists.len()];
+        for (list_id, candidates) in &self.elected {
+            let n = totals
+                .lists
+                .iter()
+                .position(|x| x.id == *list_id)
+                .ok_or_else(|| EmlError::Unknown(format!("list {list_id}")))?;
+            let list = &totals.lists[n];
+            for candidate in candidates {
+                let position = list
+                    .candidate_ids
+                    .iter()
+                    .position(|x| x == candidate)
+                    .ok_or_else(|| EmlError::Unknown(format!("candidate {list_id}.{candidate}")))?;
+                elected[n].push(Elected {
+                    list: n,
+                    position,
+                    preference: threshold
+                        .is_some_and(|x| frac(list.candidates[position].votes.0, 1) >= x),
+                });
+            }
+        }
+        Ok(elected)
+    }
+}
+
+/// Read the number of seats awarded to every list in an EML 520 file
+pub fn read_eml_520(xml: &str) -> Result<Vec<ContestSeats>, EmlError> {
+    let document = Element::parse(xml)?;
+    let election = eml_kind(&document, "520")?.find(&["Result", "Election"])?;
+    let id = ElectionId::read(election)?;
+    let header = EmlHeader::read(&document);
+
+    election
+        .children("Contest")
+        .map(|contest| {
+            let mut seats = Vec::<(String, Count)>::new();
+            let mut elected = Vec::<(String, Vec<(Count, String)>)>::new();
+            for selection in contest.children("Selection") {
+                if let Some(affiliation) = selection.child("AffiliationIdentifier") {
+                    let id = affiliation.attribute("Id").unwrap_or_default();
+                    seats.push((id.to_string(), 0));
+                    elected.push((id.to_string(), Vec::new()));
+                } else if selection
+                    .child("Elected")
+                    .is_some_and(|x| x.text.trim() == "yes")
+                {
+                    seats
+                        .last_mut()
+                        .ok_or(EmlError::Missing("AffiliationIdentifier"))?
+                        .1 += 1;
+                    let candidate = selection.find(&["Candidate", "CandidateIdentifier"])?;
+                    let ranking = match selection.child("Ranking") {
+                        Some(ranking) => ranking.count()?,
+                        None => Count::MAX,
+                    };
+                    elected.last_mut().unwrap().1.push((
+                        ranking,
+                        candidate.attribute("Id").unwrap_or_default().to_string(),
+                    ));
+                }
+            }
+
+            Ok(ContestSeats {
+                header: header.clone(),
+                election: id.clone(),
+                contest: contest
+                    .find(&["ContestIdentifier"])?
+                    .attribute("Id")
+                    .unwrap_or_default()
+                    .to_string(),
+                seats,
+                elected: elected
+                    .into_iter()
+                    .map(|(list, mut candidates)| {
+                        // candidates without a ranking keep their order on the list
+                        candidates.sort_by_key(|(ranking, _)| *ranking);
+                        (list, candidates.into_iter().map(|(_, id)| id).collect())
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Write the outcome of an apportionment as an EML 520 document ("resultaat"). The election,
+/// contest and lists are identified as in the EML 510 `totals` on which the apportionment was
+/// based, and `seats` are the seats awarded to each of its lists. Unless the `elected` candidates
+/// are given, they are determined from the preference votes in `totals` (see `Totals::elected`).
+/// The transaction identifier, issue date and creation time are copied from `totals`, so that the
+/// output only depends on the source document.
+pub fn write_eml_520(totals: &Totals, seats: &[Seats], elected: Option<&[Vec<Elected>]>) -> String {
+    use quick_xml::escape::escape;
+    use std::fmt::Write;
+
+    let computed;
+    let elected = match elected {
+        Some(elected) => elected,
+        None => {
+            computed = totals.elected(seats);
+            &computed
+        }
+    };
+
+    let id = &totals.election;
+    let mut out = String::new();
+    let mut line = |indent: usize, text: &str| {
+        writeln!(out, "{:indent$}{text}", "", indent = 2 * indent).unwrap();
+    };
+
+    line(0, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    line(
+        0,
+        concat!(
+            r#"<EML xmlns="urn:oasis:names:tc:evs:schema:eml" "#,
+            r#"xmlns:kr="http://www.kiesraad.nl/extensions" "#,
+            r#"xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" "#,
+            r#"Id="520" SchemaVersion="5" "#,
+            r#"xsi:schemaLocation="urn:oasis:names:tc:evs:schema:eml 520-result-v5-0.xsd "#,
+            r#"http://www.kiesraad.nl/extensions kiesraad-eml-extensions.xsd">"#
+        ),
+    );
+    let header = &totals.header;
+    line(
+        1,
+        &format!(
+            "<TransactionId>{}</TransactionId>",
+            escape(header.transaction_id.as_deref().unwrap_or("1"))
+        ),
+    );
+    if let Some(issue_date) = &header.issue_date {
+        line(
+            1,
+            &format!("<IssueDate>{}</IssueDate>", escape(issue_date.as_str())),
+        );
+    }
+    if let Some(created) = &header.creation_date_time {
+        line(
+            1,
+            &format!(
+                "<kr:CreationDateTime>{}</kr:CreationDateTime>",
+                escape(created.as_str())
+            ),
+        );
+    }
+    line(1, "<Result>");
+    line(2, "<Election>");
+    line(
+        3,
+        &format!(r#"<ElectionIdentifier Id="{}">"#, escape(id.id.as_str())),
+    );
+    line(
+        4,
+        &format!("<ElectionName>{}</ElectionName>", escape(id.name.as_str())),
+    );
+    line(
+        4,
+        &format!(
+            "<ElectionCategory>{}</ElectionCategory>",
+            escape(id.category.as_str())
+        ),
+    );
+    if let Some(subcategory) = &id.subcategory {
+        line(
+            4,
+            &format!(
+                "<kr:ElectionSubcategory>{}</kr:ElectionSubcategory>",
+                escape(subcategory.as_str())
+            ),
+        );
+    }
+    if let Some(domain) = &id.domain {
+        let domain = escape(domain.as_str());
+        match &id.domain_id {
+            Some(code) => line(
+                4,
+                &format!(
+                    r#"<kr:ElectionDomain Id="{}">{domain}</kr:ElectionDomain>"#,
+                    escape(code.as_str())
+                ),
+            ),
+            None => line(
+                4,
+                &format!("<kr:ElectionDomain>{domain}</kr:ElectionDomain>"),
+            ),
+        }
+    }
+    if let Some(date) = &id.date {
+        line(
+            4,
+            &format!(
+                "<kr:ElectionDate>{}</kr:ElectionDate>",
+                escape(date.as_str())
+            ),
+        );
+    }
+    line(3, "</ElectionIdentifier>");
+    line(3, "<Contest>");
+    line(
+        4,
+        &format!(
+            r#"<ContestIdentifier Id="{}"/>"#,
+            escape(totals.contest.as_str())
+        ),
+    );
+
+    for (n, list) in totals.lists.iter().enumerate() {
+        line(4, "<Selection>");
+        line(
+            5,
+            &format!(
+                r#"<AffiliationIdentifier Id="{}">"#,
+                escape(list.id.as_str())
+            ),
+        );
+        line(
+            6,
+            &format!(
+                "<RegisteredName>{}</RegisteredName>",
+                escape(list.name.as_str())
+            ),
+        );
+        line(5, "</AffiliationIdentifier>");
+        line(4, "</Selection>");
+
+        let list_elected = elected.get(n).map(Vec::as_slice).unwrap_or_default();
+        for (position, candidate) in list.candidate_ids.iter().enumerate() {
+            let ranking = list_elected.iter().position(|x| x.position == position);
+            line(4, "<Selection>");
+            line(5, "<Candidate>");
+            line(
+                6,
+                &format!(
+                    r#"<CandidateIdentifier Id="{}"/>"#,
+                    escape(candidate.as_str())
+                ),
+            );
+            line(5, "</Candidate>");
+            if let Some(ranking) = ranking {
+                line(5, &format!("<Ranking>{}</Ranking>", ranking + 1));
+            }
+            line(
+                5,
+                &format!(
+                    "<Elected>{}</Elected>",
+                    if ranking.is_some() { "yes" } else { "no" }
+                ),
+            );
+            line(4, "</Selection>");
+        }
+    }
+
+    line(3, "</Contest>");
+    line(2, "</Election>");
+    line(1, "</Result>");
+    line(0, "</EML>");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOTALS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<EML xmlns="urn:oasis:names:tc:evs:schema:eml" xmlns:kr="http://www.kiesraad.nl/extensions" Id="510" SchemaVersion="5">
+  <TransactionId>42</TransactionId>
+  <IssueDate>2026-03-20</IssueDate>
+  <kr:CreationDateTime>2026-03-20T12:00:00.000</kr:CreationDateTime>
+  <Count><Election>
+    <ElectionIdentifier Id="GR2026_Test"><ElectionName>Gemeenteraad Test 2026</ElectionName><ElectionCategory>GR</ElectionCategory></ElectionIdentifier>
+    <Contests><Contest><ContestIdentifier Id="geen"/><TotalVotes>
+      <Selection><AffiliationIdentifier Id="1"><RegisteredName>A &amp; B</RegisteredName></AffiliationIdentifier><ValidVotes>700</ValidVotes></Selection>
+      <Selection><Candidate><CandidateIdentifier Id="1"/></Candidate><ValidVotes>400</ValidVotes></Selection>
+      <Selection><Candidate><CandidateIdentifier Id="2"/></Candidate><ValidVotes>50</ValidVotes></Selection>
+      <Selection><Candidate><CandidateIdentifier Id="3"/></Candidate><ValidVotes>250</ValidVotes></Selection>
+      <Selection><AffiliationIdentifier Id="2"><RegisteredName>C</RegisteredName></AffiliationIdentifier><ValidVotes>300</ValidVotes></Selection>
+      <Selection><Candidate><CandidateIdentifier Id="1"/></Candidate><ValidVotes>300</ValidVotes></Selection>
+      <RejectedVotes ReasonCode="blanco">3</RejectedVotes>
+    </TotalVotes></Contest></Contests>
+  </Election></Count>
+</EML>"#;
+
+    fn totals() -> Totals {
+        read_eml_510(TOTALS).unwrap().remove(0)
+    }
+
+    #[test]
+    fn copies_the_header_of_the_source() {
+        let totals = totals();
+        assert_eq!(
+            totals.header,
+            EmlHeader {
+                transaction_id: Some("42".to_string()),
+                issue_date: Some("2026-03-20".to_string()),
+                creation_date_time: Some("2026-03-20T12:00:00.000".to_string()),
+            }
+        );
+
+        let result = read_eml_520(&write_eml_520(
+            &totals,
+            &[Seats::filled(2), Seats::filled(1)],
+            None,
+        ));
+        assert_eq!(result.unwrap()[0].header, totals.header);
+    }
+
+    #[test]
+    fn round_trip() {
+        let totals = totals();
+        let seats = [Seats::filled(2), Seats::filled(1)];
+        let result = read_eml_520(&write_eml_520(&totals, &seats, None))
+            .unwrap()
+            .remove(0);
+        assert_eq!(result.election, totals.election);
+        assert_eq!(result.contest, "geen");
+        assert_eq!(result.seats, [("1".to_string(), 2), ("2".to_string(), 1)]);
+        assert_eq!(result.elected_in(&totals).unwrap(), totals.elected(&seats));
+    }
+
+    #[test]
+    fn writes_the_given_elected_candidates() {
+        let totals = totals();
+        let seats = [Seats::filled(2), Seats::filled(1)];
+        // candidate 1.2 does not meet the preference threshold, but is elected e.g. after a withdrawal
+        let elected = vec![
+            vec![
+                Elected {
+                    list: 0,
+                    position: 0,
+                    preference: true,
+                },
+                Elected {
+                    list: 0,
+                    position: 1,
+                    preference: false,
+                },
+            ],
+            vec![Elected {
+                list: 1,
+                position: 0,
+                preference: true,
+            }],
+        ];
+        let result = read_eml_520(&write_eml_520(&totals, &seats, Some(&elected)))
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            result.elected,
+            [
+                ("1".to_string(), vec!["1".to_string(), "2".to_string()]),
+                ("2".to_string(), vec!["1".to_string()]),
+            ]
+        );
+        assert_eq!(result.elected_in(&totals).unwrap(), elected);
+    }
+
+    #[test]
+    fn unknown_candidates_are_an_error() {
+        let totals = totals();
+        let mut result = read_eml_520(&write_eml_520(
+            &totals,
+            &[Seats::filled(2), Seats::filled(1)],
+            None,
+        ))
+        .unwrap()
+        .remove(0);
+        result.elected[1].1[0] = "7".to_string();
+        let err = result.elected_in(&totals).unwrap_err();
+        assert_eq!(err.to_string(), "unknown candidate 2.7");
+    }
+}
@@ -0,0 +1,289 @@
+use crate::data::*;
+use crate::kiesraad::{CsvError, CsvRegion};
+
+/// The value of a list in a Kiesraad CSV file that an erratum corrects
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErratumField {
+    /// `AantalStemmen`
+    Votes,
+    /// `AantalZetels`
+    Seats,
+    /// `AantalKandidaten`, the number of candidates on the list
+    Candidates,
+}
+
+impl std::fmt::Display for ErratumField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ErratumField::Votes => write!(f, "AantalStemmen"),
+            ErratumField::Seats => write!(f, "AantalZetels"),
+            ErratumField::Candidates => write!(f, "AantalKandidaten"),
+        }
+    }
+}
+
+/// A correction of the results in a Kiesraad CSV file, with the source that justifies it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Erratum {
+    /// The line in the errata file
+    pub line: u64,
+    /// The name or code of the region
+    pub region: String,
+    /// The name of the list, or `#` followed by its position in the region if the name is not
+    /// unique
+    pub party: String,
+    pub field: ErratumField,
+    pub value: Count,
+    /// A citation of the source of the correction, e.g. an issue of the Staatscourant
+    pub source: String,
+}
+
+/// An erratum that was applied, with the value it replaced
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Correction {
+    pub erratum: Erratum,
+    /// The original value; `None` if the number of candidates was not given
+    pub original: Option<Count>,
+}
+
+impl std::fmt::Display for Correction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let erratum = &self.erratum;
+        write!(
+            f,
+            "{}, {}: {} {} -> {} ({})",
+            erratum.region,
+            erratum.party,
+            erratum.field,
+            self.original
+                .map_or("(none)".to_string(), |x| x.to_string()),
+            erratum.value,
+            erratum.source
+        )
+    }
+}
+
+/// Read an errata file: lines of the form `Regio;Partij;Kolom;Waarde;Bron`, after a header with
+/// these names, where `Kolom` is `AantalStemmen`, `AantalZetels` or `AantalKandidaten`. Lines
+/// starting with `#` are comments.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let errata = read_errata(
+///     "Regio;Partij;Kolom;Waarde;Bron\n\
+///      ## the official report lists one more vote\n\
+///      Saba;WIPM;AantalStemmen;778;Proces-verbaal Saba, p. 2\n",
+/// )
+/// .unwrap();
+/// assert_eq!(errata[0].field, ErratumField::Votes);
+///
+/// let missing = read_errata("Regio;Partij;Kolom;Waarde;Bron\nSaba;WIPM;AantalStemmen;778;\n");
+/// assert_eq!(missing.unwrap_err().to_string(), "line 2: missing source");
+/// ```
+pub fn read_errata(text: &str) -> Result<Vec<Erratum>, CsvError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b';')
+        .comment(Some(b'#'))
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let header = reader.headers()?.clone();
+    if !header
+        .iter()
+        .map(str::trim)
+        .eq(["Regio", "Partij", "Kolom", "Waarde", "Bron"])
+    {
+        return Err(CsvError::Header(
+            header.iter().collect::<Vec<_>>().join(";"),
+        ));
+    }
+
+    let mut errata = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |x| x.line());
+        let problem = |problem: String| CsvError::Row { line, problem };
+        if record.len() != 5 {
+            return Err(problem(format!(
+                "expected 5 columns, found {}",
+                record.len()
+            )));
+        }
+
+        let field = match record[2].trim() {
+            "AantalStemmen" => ErratumField::Votes,
+            "AantalZetels" => ErratumField::Seats,
+            "AantalKandidaten" => ErratumField::Candidates,
+            column => return Err(problem(format!("unknown Kolom: {column}"))),
+        };
+        let value = record[3]
+            .trim()
+            .parse()
+            .map_err(|_| problem(format!("Waarde is not a number: {}", &record[3])))?;
+        let source = record[4].trim();
+        if source.is_empty() {
+            return Err(problem("missing source".to_string()));
+        }
+
+        errata.push(Erratum {
+            line,
+            region: record[0].trim().to_string(),
+            party: record[1].trim().to_string(),
+            field,
+            value,
+            source: source.to_string(),
+        });
+    }
+
+    Ok(errata)
+}
+
+/// Apply the `errata` to the `regions`, returning the corrections that were made. Every erratum
+/// has to identify exactly one list.
+pub fn apply_errata(
+    regions: &mut [CsvRegion],
+    errata: &[Erratum],
+) -> Result<Vec<Correction>, CsvError> {
+    errata
+        .iter()
+        .map(|erratum| {
+            let problem = |problem: String| CsvError::Row {
+                line: erratum.line,
+                problem,
+            };
+            let region = regions
+                .iter_mut()
+                .find(|x| x.name == erratum.region || x.code == erratum.region)
+                .ok_or_else(|| problem(format!("no region {}", erratum.region)))?;
+
+            let list = find_list(region, &erratum.party).map_err(problem)?;
+            let list = &mut region.lists[list];
+
+            let original = match erratum.field {
+                ErratumField::Votes => Some(std::mem::replace(&mut list.votes.0, erratum.value)),
+                ErratumField::Seats => Some(std::mem::replace(&mut list.seats, erratum.value)),
+                ErratumField::Candidates => list.candidates.replace(erratum.value),
+            };
+            Ok(Correction {
+                erratum: erratum.clone(),
+                original,
+            })
+        })
+        .collect()
+}
+
+/// The index of the list in the `region` that is named `party`, or that is at the position given
+/// as `#` followed by a number
+pub(crate) fn find_list(region: &CsvRegion, party: &str) -> Result<usize, String> {
+    let position = party
+        .strip_prefix('#')
+        .and_then(|x| x.parse::<usize>().ok());
+    let matches = region
+        .lists
+        .iter()
+        .enumerate()
+        .filter(|(i, x)| match position {
+            Some(position) => i + 1 == position,
+            None => x.name == party,
+        })
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    match matches[..] {
+        [i] => Ok(i),
+        [] => Err(format!("no list {party}")),
+        _ => Err(format!(
+            "several lists are named {party}; use # and the position of the list"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kiesraad::read_kiesraad_csv;
+
+    fn regions() -> Vec<CsvRegion> {
+        read_kiesraad_csv(
+            "Regio;Code;Type;Partij;AantalStemmen;AantalZetels\n\
+             Nederland;NL;Partij;RKSP;3000;2\n\
+             Nederland;NL;Partij;Blanco;500;0\n\
+             Nederland;NL;Partij;Blanco;400;0\n",
+        )
+        .unwrap()
+    }
+
+    fn apply(errata: &str) -> Result<Vec<Correction>, String> {
+        let errata = read_errata(&format!("Regio;Partij;Kolom;Waarde;Bron\n{errata}")).unwrap();
+        apply_errata(&mut regions(), &errata).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn corrections() {
+        let mut regions = regions();
+        let errata = read_errata(
+            "Regio;Partij;Kolom;Waarde;Bron\n\
+             Nederland;RKSP;AantalStemmen;3100;Staatscourant\n\
+             NL;#3;AantalZetels;1;Staatscourant\n\
+             Nederland;RKSP;AantalKandidaten;20;Staatscourant\n",
+        )
+        .unwrap();
+        let corrections = apply_errata(&mut regions, &errata).unwrap();
+        assert_eq!(regions[0].votes(), [Votes(3100), Votes(500), Votes(400)]);
+        assert_eq!(regions[0].lists[2].seats, 1);
+        assert_eq!(regions[0].lists[0].candidates, Some(20));
+
+        let corrections = corrections
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            corrections,
+            [
+                "Nederland, RKSP: AantalStemmen 3000 -> 3100 (Staatscourant)",
+                "NL, #3: AantalZetels 0 -> 1 (Staatscourant)",
+                "Nederland, RKSP: AantalKandidaten (none) -> 20 (Staatscourant)",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_region_or_party() {
+        let region = apply("Limburg;RKSP;AantalStemmen;3100;Staatscourant\n");
+        assert_eq!(region.unwrap_err(), "line 2: no region Limburg");
+        let party = apply("Nederland;ARP;AantalStemmen;3100;Staatscourant\n");
+        assert_eq!(party.unwrap_err(), "line 2: no list ARP");
+        let position = apply("Nederland;#4;AantalStemmen;3100;Staatscourant\n");
+        assert_eq!(position.unwrap_err(), "line 2: no list #4");
+        let ambiguous = apply("Nederland;Blanco;AantalStemmen;3100;Staatscourant\n");
+        assert_eq!(
+            ambiguous.unwrap_err(),
+            "line 2: several lists are named Blanco; use # and the position of the list"
+        );
+    }
+
+    #[test]
+    fn malformed_errata() {
+        let error = |text: &str| read_errata(text).unwrap_err().to_string();
+        assert_eq!(
+            error("Regio;Partij;Kolom;Waarde\n"),
+            "unexpected header: Regio;Partij;Kolom;Waarde"
+        );
+        let header = "Regio;Partij;Kolom;Waarde;Bron\n";
+        assert_eq!(
+            error(&format!("{header}NL;RKSP;Stemmen;3100;Staatscourant\n")),
+            "line 2: unknown Kolom: Stemmen"
+        );
+        assert_eq!(
+            error(&format!(
+                "{header}NL;RKSP;AantalStemmen;3.100;Staatscourant\n"
+            )),
+            "line 2: Waarde is not a number: 3.100"
+        );
+        assert_eq!(
+            error(&format!("{header}NL;RKSP;AantalStemmen;3100\n")),
+            "line 2: expected 5 columns, found 4"
+        );
+    }
+}
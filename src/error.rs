@@ -0,0 +1,79 @@
+use crate::data::Count;
+
+/// The reasons why a seat apportionment cannot be performed
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let mut ballot = LowestListNumber;
+/// let mut ctx = Context::new(&mut ballot);
+///
+/// let votes = [Votes(Count::MAX / 64), Votes(Count::MAX / 128)];
+/// let mut seats = [Seats::unlimited(); 2];
+/// assert_eq!(allocate(Seats::filled(7), &votes, &mut seats, &mut ctx), Ok(()));
+/// assert_eq!(seats.map(|x| x.count()), [5, 2]);
+///
+/// let votes = [Votes(Count::MAX), Votes(1)];
+/// let mut seats = [Seats::unlimited(); 2];
+/// let result = allocate(Seats::filled(7), &votes, &mut seats, &mut ctx);
+/// assert_eq!(result, Err(AllocationError::Overflow));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AllocationError {
+    /// There are no parties, or no seats to apportion
+    EmptyInput,
+    /// No votes were cast, so there is no quota
+    ZeroVotes,
+    /// The votes and seats do not describe the same number of parties
+    LengthMismatch { votes: usize, seats: usize },
+    /// A list combination or party refers to a list that does not exist
+    UnknownList(usize),
+    /// Seats remain, but no party is eligible for them (for instance because every list is
+    /// exhausted)
+    UnfillableSeats { remaining: Count },
+    /// The quota is so small that the parties together have more whole quotas than there are
+    /// seats
+    QuotaTooSmall { excess: Count },
+    /// Parties were tied for a seat, but the ballot did not draw a winner
+    LotNotDrawn { tied: Vec<usize> },
+    /// The numbers involved are too large to be computed with
+    Overflow,
+    /// A fraction would have a denominator of zero, e.g. a quota without seats
+    DivisionByZero,
+}
+
+impl std::fmt::Display for AllocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            AllocationError::EmptyInput => write!(f, "there are no parties or no seats"),
+            AllocationError::ZeroVotes => write!(f, "no votes were cast"),
+            AllocationError::LengthMismatch { votes, seats } => write!(
+                f,
+                "votes are given for {votes} parties, but seats for {seats} parties"
+            ),
+            AllocationError::UnknownList(list) => write!(f, "there is no list {}", list + 1),
+            AllocationError::UnfillableSeats { remaining: 1 } => {
+                write!(f, "a seat cannot be filled")
+            }
+            AllocationError::UnfillableSeats { remaining } => {
+                write!(f, "{remaining} seats cannot be filled")
+            }
+            AllocationError::QuotaTooSmall { excess } => write!(
+                f,
+                "the quota awards {excess} more whole seats than there are seats"
+            ),
+            AllocationError::LotNotDrawn { tied } => write!(
+                f,
+                "no lot was drawn between lists {}",
+                tied.iter()
+                    .map(|i| (i + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            AllocationError::Overflow => write!(f, "the numbers are too large"),
+            AllocationError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for AllocationError {}
@@ -0,0 +1,557 @@
+use crate::data::*;
+
+/// Something that went wrong while reading a Kiesraad CSV file
+#[derive(Debug)]
+pub enum CsvError {
+    /// The file is not well-formed CSV
+    Csv(csv::Error),
+    /// The header is not one of the known layouts
+    Header(String),
+    /// A column that is needed is not in the header
+    MissingColumn(&'static str),
+    /// A row could not be read
+    Row { line: u64, problem: String },
+}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            CsvError::Csv(err) => write!(f, "invalid CSV: {err}"),
+            CsvError::Header(header) => write!(f, "unexpected header: {header}"),
+            CsvError::MissingColumn(name) => write!(f, "missing column: {name}"),
+            CsvError::Row { line, problem } => write!(f, "line {line}: {problem}"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<csv::Error> for CsvError {
+    fn from(err: csv::Error) -> Self {
+        CsvError::Csv(err)
+    }
+}
+
+/// The layout of a Kiesraad CSV file, which is determined by the columns in its header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvLayout {
+    /// `Regio;Code;Type;Partij;AantalStemmen;AantalZetels`, as used for most elections; the type
+    /// of a row is `Partij`, `Lijstcombinatie <name>`, or one of the totals of the region
+    Regions,
+    /// `Regio;Code;Lijst;...`, where every list is identified as `Lijst<number>`; several lists of
+    /// the same party appear separately
+    Lists,
+    /// `Gemeente;Code;AmsterdamseCode;Partij;AantalStemmen;AantalZetels`, as used for municipal
+    /// elections; every row is a party
+    Municipalities,
+}
+
+impl CsvLayout {
+    /// The columns that a file in this layout has
+    pub fn columns(self) -> [&'static str; 6] {
+        let (region, kind) = match self {
+            CsvLayout::Regions => ("Regio", "Type"),
+            CsvLayout::Lists => ("Regio", "Lijst"),
+            CsvLayout::Municipalities => ("Gemeente", "AmsterdamseCode"),
+        };
+        [
+            region,
+            "Code",
+            kind,
+            "Partij",
+            "AantalStemmen",
+            "AantalZetels",
+        ]
+    }
+
+    /// The layout with the given header, if it is known: the header has to contain the columns of
+    /// the layout, in any order, and may contain other columns as well
+    pub fn detect(header: &csv::StringRecord) -> Option<CsvLayout> {
+        let columns = Columns::new(header);
+        [
+            CsvLayout::Regions,
+            CsvLayout::Lists,
+            CsvLayout::Municipalities,
+        ]
+        .into_iter()
+        .find(|layout| {
+            layout
+                .columns()
+                .iter()
+                .all(|name| columns.find(name).is_some())
+        })
+    }
+}
+
+/// The rows with the totals of a region rather than the votes for a party
+const TOTALS: [&str; 5] = [
+    "AantalBlancoStemmen",
+    "AantalGeldigeStemmen",
+    "AantalOngeldigeStemmen",
+    "Kiesgerechtigden",
+    "Opkomst",
+];
+
+/// The results of a single list in a Kiesraad CSV file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvList {
+    pub name: String,
+    pub votes: Votes,
+    /// The official number of seats; an empty column means no seats
+    pub seats: Count,
+    /// The number of candidates on the list, if it is given in the column `AantalKandidaten`, or
+    /// otherwise in the column after the seats. That column can also contain a comment, starting
+    /// with `#`.
+    pub candidates: Option<Count>,
+}
+
+/// The results of a single region in a Kiesraad CSV file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvRegion {
+    /// The name of the region, e.g. a municipality or "Nederland"
+    pub name: String,
+    /// The code of the region, e.g. "G0518"
+    pub code: String,
+    pub lists: Vec<CsvList>,
+    /// The name of every combination of lists ("lijstcombinatie"), with its members as indices
+    /// into `lists`
+    pub combinations: Vec<(String, Vec<usize>)>,
+}
+
+impl CsvRegion {
+    /// The votes per list
+    pub fn votes(&self) -> Vec<Votes> {
+        self.lists.iter().map(|x| x.votes).collect()
+    }
+
+    /// The official outcome
+    pub fn outcome(&self) -> Vec<Seats> {
+        self.lists.iter().map(|x| Seats::filled(x.seats)).collect()
+    }
+
+    /// The seats per list, limited by the number of candidates on each list where it is known
+    pub fn candidate_limits(&self) -> Vec<Seats> {
+        self.lists
+            .iter()
+            .map(|x| x.candidates.map_or(Seats::unlimited(), Seats::limited))
+            .collect()
+    }
+}
+
+/// Read the results of every region in a Kiesraad CSV file, as published on
+/// verkiezingsuitslagen.nl. Columns are identified by their name in the header, and can appear in
+/// any order; other columns are ignored. Rows of the same region must be consecutive.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let regions = read_kiesraad_csv(
+///     "Regio;Code;Type;Partij;AantalStemmen;AantalZetels;\n\
+///      Saba;O9003;AantalGeldigeStemmen;;1000;;\n\
+///      Saba;O9003;Partij;WIPM;777;4;\n\
+///      Saba;O9003;Partij;S.L.P.;223;1;\n",
+/// )
+/// .unwrap();
+/// assert_eq!(regions[0].votes(), [Votes(777), Votes(223)]);
+///
+/// let err = read_kiesraad_csv("Regio;Code;Type;Partij;AantalStemmen;AantalZetels;\nSaba;O9003;Partij;WIPM;many;4;\n");
+/// assert_eq!(err.unwrap_err().to_string(), "line 2: AantalStemmen is not a number: many");
+///
+/// let reordered = read_kiesraad_csv(
+///     "Partij;AantalZetels;Regio;Opmerking;AantalStemmen;Type;Code\n\
+///      WIPM;4;Saba;;777;Partij;O9003\n\
+///      S.L.P.;1;Saba;herteld;223;Partij;O9003\n",
+/// )
+/// .unwrap();
+/// assert_eq!(reordered, regions);
+/// ```
+pub fn read_kiesraad_csv(text: &str) -> Result<Vec<CsvRegion>, CsvError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b';')
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let header = reader.headers()?.clone();
+    let layout = CsvLayout::detect(&header)
+        .ok_or_else(|| CsvError::Header(header.iter().collect::<Vec<_>>().join(";")))?;
+    let columns = Columns::new(&header);
+    let [
+        region_name,
+        code_name,
+        kind_name,
+        party_name,
+        votes_name,
+        seats_name,
+    ] = layout.columns();
+    let region_column = columns.require(region_name)?;
+    let code_column = columns.require(code_name)?;
+    let kind_column = columns.require(kind_name)?;
+    let party_column = columns.require(party_name)?;
+    let votes_column = columns.require(votes_name)?;
+    let seats_column = columns.require(seats_name)?;
+    // without a column for them, the numbers of candidates can be in an unnamed column after the
+    // seats
+    let candidates_column = columns.find("AantalKandidaten").or_else(|| {
+        let next = seats_column + 1;
+        columns
+            .0
+            .get(next)
+            .is_none_or(|x| x.is_empty())
+            .then_some(next)
+    });
+
+    let mut regions = Vec::<CsvRegion>::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |x| x.line());
+        let problem = |problem: String| CsvError::Row { line, problem };
+
+        let name = field(&record, region_column, region_name)?;
+        let code = field(&record, code_column, code_name)?;
+        let kind = field(&record, kind_column, kind_name)?;
+        if layout != CsvLayout::Municipalities && TOTALS.contains(&kind) {
+            continue;
+        }
+        let combination = match layout {
+            CsvLayout::Regions => match kind.strip_prefix("Lijstcombinatie") {
+                Some(label) => Some(label.trim()),
+                None if kind == "Partij" => None,
+                None => return Err(problem(format!("unknown {kind_name}: {kind}"))),
+            },
+            CsvLayout::Lists => match kind.strip_prefix("Lijst") {
+                Some(number) if number.parse::<Count>().is_ok() => None,
+                _ => return Err(problem(format!("unknown {kind_name}: {kind}"))),
+            },
+            CsvLayout::Municipalities => None,
+        };
+
+        let list = CsvList {
+            name: record.get(party_column).unwrap_or_default().to_string(),
+            votes: Votes(number(&record, votes_column, votes_name)?),
+            seats: match field(&record, seats_column, seats_name)? {
+                "" => 0,
+                _ => number(&record, seats_column, seats_name)?,
+            },
+            candidates: match candidates_column
+                .and_then(|column| Some((column, record.get(column)?.trim())))
+            {
+                None | Some((_, "")) => None,
+                Some((_, comment)) if comment.starts_with('#') => None,
+                Some((column, _)) => Some(number(&record, column, "the number of candidates")?),
+            },
+        };
+
+        let region = match regions.last_mut() {
+            Some(region) if region.code == code => region,
+            _ => {
+                if regions.iter().any(|x| x.code == code) {
+                    return Err(problem(format!(
+                        "the rows of region {code} are not consecutive"
+                    )));
+                }
+                regions.push(CsvRegion {
+                    name: name.to_string(),
+                    code: code.to_string(),
+                    lists: Vec::new(),
+                    combinations: Vec::new(),
+                });
+                regions.last_mut().unwrap()
+            }
+        };
+        if let Some(label) = combination {
+            let index = region.lists.len();
+            match region.combinations.iter_mut().find(|(x, _)| x == label) {
+                Some((_, members)) => members.push(index),
+                None => region.combinations.push((label.to_string(), vec![index])),
+            }
+        }
+        region.lists.push(list);
+    }
+
+    Ok(regions)
+}
+
+/// The position of every column in a CSV file, by its name in the header
+struct Columns(Vec<String>);
+
+impl Columns {
+    fn new(header: &csv::StringRecord) -> Columns {
+        Columns(
+            header
+                .iter()
+                .map(|x| x.trim_start_matches('\u{feff}').trim().to_string())
+                .collect(),
+        )
+    }
+
+    /// The position of the column with this name, if there is one
+    fn find(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|x| x == name)
+    }
+
+    /// The position of the column with this name, which has to be present
+    fn require(&self, name: &'static str) -> Result<usize, CsvError> {
+        self.find(name).ok_or(CsvError::MissingColumn(name))
+    }
+}
+
+/// The field in the given column of a record; a column that is missing from a row is an error
+fn field<'a>(
+    record: &'a csv::StringRecord,
+    column: usize,
+    name: &str,
+) -> Result<&'a str, CsvError> {
+    record
+        .get(column)
+        .map(str::trim)
+        .ok_or_else(|| CsvError::Row {
+            line: record.position().map_or(0, |x| x.line()),
+            problem: format!("missing {name}"),
+        })
+}
+
+/// A number in the given column of a record
+fn number(record: &csv::StringRecord, column: usize, name: &str) -> Result<Count, CsvError> {
+    let text = field(record, column, name)?;
+    text.parse::<Count>().map_err(|_| CsvError::Row {
+        line: record.position().map_or(0, |x| x.line()),
+        problem: format!("{name} is not a number: {text}"),
+    })
+}
+
+/// The lists that parties submitted in the kieskringen of an election for the Tweede Kamer, with
+/// their votes in every kieskring (see `allocate_party_lists`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvPartyLists {
+    /// The name of every kieskring
+    pub kieskringen: Vec<String>,
+    /// The party and the name of every list
+    pub lists: Vec<(String, String)>,
+    /// The lists of every party, as indices into `lists`
+    pub parties: Vec<Vec<usize>>,
+    /// `votes[k][i]` is the number of votes for list `i` in kieskring `k`
+    pub votes: Vec<Vec<Votes>>,
+    /// The seats of every list, limited by its number of candidates if that is given
+    pub seats: Vec<Seats>,
+}
+
+/// Read the votes for the lists of every party per kieskring from a CSV file with the columns
+/// `Kieskring`, `Partij`, `Lijst` and `AantalStemmen`, and optionally `AantalKandidaten`, in any
+/// order. A list is identified by its party and name; an empty number of candidates means that it
+/// is not known.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let lists = read_party_lists_csv(
+///     "Kieskring;Partij;Lijst;AantalStemmen;AantalKandidaten\n\
+///      Groningen;RKSP;A;100;10\n\
+///      Leeuwarden;RKSP;B;50;\n\
+///      Groningen;ARP;A;80;\n",
+/// )
+/// .unwrap();
+/// assert_eq!(lists.parties, [vec![0, 1], vec![2]]);
+/// assert_eq!(lists.votes[1], [Votes(0), Votes(50), Votes(0)]);
+///
+/// let err = read_party_lists_csv("Kieskring;Partij;Lijst\nGroningen;RKSP;A\n");
+/// assert_eq!(err.unwrap_err().to_string(), "missing column: AantalStemmen");
+/// ```
+pub fn read_party_lists_csv(text: &str) -> Result<CsvPartyLists, CsvError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b';')
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let columns = Columns::new(reader.headers()?);
+    let kieskring_column = columns.require("Kieskring")?;
+    let party_column = columns.require("Partij")?;
+    let list_column = columns.require("Lijst")?;
+    let votes_column = columns.require("AantalStemmen")?;
+    let candidates_column = columns.find("AantalKandidaten");
+
+    let mut result = CsvPartyLists {
+        kieskringen: Vec::new(),
+        lists: Vec::new(),
+        parties: Vec::new(),
+        votes: Vec::new(),
+        seats: Vec::new(),
+    };
+    let mut party_names = Vec::<String>::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |x| x.line());
+        let kieskring = field(&record, kieskring_column, "Kieskring")?;
+        let party = field(&record, party_column, "Partij")?;
+        let list = (
+            party.to_string(),
+            field(&record, list_column, "Lijst")?.to_string(),
+        );
+        let votes = number(&record, votes_column, "AantalStemmen")?;
+        let seats = match candidates_column {
+            Some(column) if !field(&record, column, "AantalKandidaten")?.is_empty() => {
+                Seats::limited(number(&record, column, "AantalKandidaten")?)
+            }
+            _ => Seats::unlimited(),
+        };
+
+        let k = match result.kieskringen.iter().position(|x| x == kieskring) {
+            Some(k) => k,
+            None => {
+                result.kieskringen.push(kieskring.to_string());
+                result.votes.push(vec![Votes(0); result.lists.len()]);
+                result.kieskringen.len() - 1
+            }
+        };
+        let i = match result.lists.iter().position(|x| *x == list) {
+            Some(i) if result.seats[i].limit != seats.limit => {
+                return Err(CsvError::Row {
+                    line,
+                    problem: format!(
+                        "the number of candidates of list {} of {} differs from an earlier row",
+                        list.1, list.0
+                    ),
+                });
+            }
+            Some(i) => i,
+            None => {
+                result.lists.push(list);
+                result.seats.push(seats);
+                for kieskring in &mut result.votes {
+                    kieskring.push(Votes(0));
+                }
+                let i = result.lists.len() - 1;
+                match party_names.iter().position(|x| x == party) {
+                    Some(n) => result.parties[n].push(i),
+                    None => {
+                        party_names.push(party.to_string());
+                        result.parties.push(vec![i]);
+                    }
+                }
+                i
+            }
+        };
+        result.votes[k][i].0 =
+            result.votes[k][i]
+                .0
+                .checked_add(votes)
+                .ok_or_else(|| CsvError::Row {
+                    line,
+                    problem: "too many votes".to_string(),
+                })?;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Regio;Code;Type;Partij;AantalStemmen;AantalZetels;AantalKandidaten\n";
+
+    fn error(text: &str) -> String {
+        read_kiesraad_csv(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn layouts() {
+        let header = |text: &str| {
+            CsvLayout::detect(&csv::StringRecord::from(
+                text.split(';').collect::<Vec<_>>(),
+            ))
+        };
+        assert_eq!(header(HEADER.trim()), Some(CsvLayout::Regions));
+        assert_eq!(
+            header("Gemeente;Code;AmsterdamseCode;Partij;AantalStemmen;AantalZetels"),
+            Some(CsvLayout::Municipalities)
+        );
+        assert_eq!(
+            header("\u{feff}Regio;Code;Lijst;Partij;AantalStemmen;AantalZetels"),
+            Some(CsvLayout::Lists)
+        );
+        assert_eq!(header("Regio;Code;Partij;AantalStemmen;AantalZetels"), None);
+    }
+
+    #[test]
+    fn municipalities() {
+        let regions = read_kiesraad_csv(
+            "Gemeente;Code;AmsterdamseCode;Partij;AantalStemmen;AantalZetels\n\
+             Vaals;G0981;;VVD;2100;3\n\
+             Vaals;G0981;;CDA;1900;;\n\
+             Eijsden-Margraten;G1903;;CDA;5000;7\n",
+        )
+        .unwrap();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].name, "Vaals");
+        assert_eq!(regions[0].votes(), [Votes(2100), Votes(1900)]);
+        assert_eq!(regions[0].lists[1].seats, 0);
+        assert_eq!(regions[1].code, "G1903");
+    }
+
+    #[test]
+    fn regions_and_combinations() {
+        let regions = read_kiesraad_csv(&format!(
+            "{HEADER}\
+             Nederland;NL;Kiesgerechtigden;;5000;;\n\
+             Nederland;NL;Partij;RKSP;3000;2;10\n\
+             Nederland;NL;Lijstcombinatie Rechts;ARP;1200;1;\n\
+             Nederland;NL;Lijstcombinatie Rechts;CHU;800;0;# niet vermeld\n",
+        ))
+        .unwrap();
+        let region = &regions[0];
+        assert_eq!(region.votes(), [Votes(3000), Votes(1200), Votes(800)]);
+        assert_eq!(region.combinations, [("Rechts".to_string(), vec![1, 2])]);
+        assert_eq!(
+            region.candidate_limits(),
+            [Seats::limited(10), Seats::unlimited(), Seats::unlimited()]
+        );
+    }
+
+    #[test]
+    fn unparseable_numbers() {
+        let votes =
+            format!("{HEADER}Saba;O9003;Partij;WIPM;777;4;\nSaba;O9003;Partij;S.L.P.;2,23;1;\n");
+        assert_eq!(error(&votes), "line 3: AantalStemmen is not a number: 2,23");
+        let seats = format!("{HEADER}Saba;O9003;Partij;WIPM;777;vier;\n");
+        assert_eq!(error(&seats), "line 2: AantalZetels is not a number: vier");
+        let candidates = format!("{HEADER}Saba;O9003;Partij;WIPM;777;4;-1\n");
+        assert_eq!(
+            error(&candidates),
+            "line 2: the number of candidates is not a number: -1"
+        );
+    }
+
+    #[test]
+    fn missing_columns() {
+        assert_eq!(
+            error("Regio;Code;Type;Partij;AantalStemmen\nSaba;O9003;Partij;WIPM;777\n"),
+            "unexpected header: Regio;Code;Type;Partij;AantalStemmen"
+        );
+        assert_eq!(
+            error(&format!("{HEADER}Saba;O9003;Partij;WIPM;777\n")),
+            "line 2: missing AantalZetels"
+        );
+    }
+
+    #[test]
+    fn unknown_type() {
+        let text = format!("{HEADER}Saba;O9003;Lijst;WIPM;777;4;\n");
+        assert_eq!(error(&text), "line 2: unknown Type: Lijst");
+    }
+
+    #[test]
+    fn regions_are_consecutive() {
+        let text = format!(
+            "{HEADER}\
+             Saba;O9003;Partij;WIPM;777;4;\n\
+             Bonaire;O9001;Partij;MPB;3000;5;\n\
+             Saba;O9003;Partij;S.L.P.;223;1;\n"
+        );
+        assert_eq!(
+            error(&text),
+            "line 4: the rows of region O9003 are not consecutive"
+        );
+    }
+}
@@ -1,21 +1,160 @@
+mod candidates;
 mod data;
+mod divergence;
+mod divisor;
+mod election;
+#[cfg(feature = "eml")]
+mod eml;
+#[cfg(feature = "validate")]
+mod errata;
+mod error;
+#[cfg(feature = "validate")]
+mod kiesraad;
+#[cfg(feature = "validate")]
+mod lots;
+mod quota;
+mod registry;
+mod report;
+mod rules;
+mod succession;
+mod threshold;
+mod trace;
 
+pub use candidates::*;
 pub use data::*;
+pub use divergence::*;
+pub use divisor::*;
+pub use election::*;
+#[cfg(feature = "eml")]
+pub use eml::*;
+#[cfg(feature = "validate")]
+pub use errata::*;
+pub use error::*;
+#[cfg(feature = "validate")]
+pub use kiesraad::*;
+#[cfg(feature = "validate")]
+pub use lots::*;
+pub use quota::*;
+pub use registry::*;
+pub use report::*;
+pub use rules::*;
 use std::iter;
+pub use succession::*;
+pub use threshold::*;
+pub use trace::*;
+
+/// The circumstances under which a seat apportionment takes place: how lots are drawn, how the
+/// law is interpreted, and optionally, who is following the steps that are taken.
+pub struct Context<'a> {
+    pub ballot: &'a mut dyn Ballot,
+    pub observer: Option<&'a mut dyn Observer>,
+    pub rules: Rules,
+    /// An electoral threshold for the parties, in addition to any that the method imposes
+    pub threshold: Option<Threshold>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(ballot: &'a mut dyn Ballot) -> Self {
+        Context {
+            ballot,
+            observer: None,
+            rules: Rules::default(),
+            threshold: None,
+        }
+    }
+
+    pub fn observed(ballot: &'a mut dyn Ballot, observer: &'a mut dyn Observer) -> Self {
+        Context {
+            ballot,
+            observer: Some(observer),
+            rules: Rules::default(),
+            threshold: None,
+        }
+    }
+
+    pub fn with_rules(self, rules: impl Into<Rules>) -> Self {
+        Context {
+            rules: rules.into(),
+            ..self
+        }
+    }
+
+    pub fn with_threshold(self, threshold: Threshold) -> Self {
+        Context {
+            threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    fn notify(&mut self, event: Event) {
+        if let Some(observer) = &mut self.observer {
+            observer.event(event);
+        }
+    }
+
+    /// Perform an apportionment for a subset of the parties, where party `i` in the subset is
+    /// party `parties[i]` in this context.
+    fn restricted<R>(&mut self, parties: &[usize], f: impl FnOnce(&mut Context) -> R) -> R {
+        struct Restricted<'a, T: ?Sized> {
+            inner: &'a mut T,
+            parties: &'a [usize],
+        }
+
+        impl Ballot for Restricted<'_, dyn Ballot + '_> {
+            fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+                let tied = tied.iter().map(|&i| self.parties[i]).collect::<Vec<_>>();
+                let winner = self.inner.draw(&tied)?;
+                self.parties.iter().position(|&i| i == winner)
+            }
+        }
+
+        impl Observer for Restricted<'_, dyn Observer + '_> {
+            fn event(&mut self, event: Event) {
+                self.inner.event(event.map_parties(|i| self.parties[i]));
+            }
+        }
+
+        let mut ballot = Restricted {
+            inner: &mut *self.ballot,
+            parties,
+        };
+        match &mut self.observer {
+            Some(observer) => {
+                let mut observer = Restricted {
+                    inner: &mut **observer,
+                    parties,
+                };
+                f(&mut Context {
+                    threshold: self.threshold,
+                    ..Context::observed(&mut ballot, &mut observer).with_rules(self.rules)
+                })
+            }
+            None => f(&mut Context {
+                threshold: self.threshold,
+                ..Context::new(&mut ballot).with_rules(self.rules)
+            }),
+        }
+    }
+}
 
 /// This performs one step in an apportionment algorithm, allocating seats based on a
 /// "criterion" for how 'worthy' a certain party in the `seats` list is to receive the seats.
 /// It is a **requirement** that the `criterion` algorithm will always rank a party that is
 /// eligible for at least one more "seat" above a party that doesn't.
 /// The `criterion` can signal that a party isn't eligible for seats by returning `None`.
-pub fn allocation_winner<Quality: Ord>(
+/// If several parties are equally worthy, the ballot decides which one receives the seat.
+/// Returns `None` if no party is eligible.
+pub fn allocation_winner<Quality: Ord + Clone + Into<Fraction>>(
     votes: &[Votes],
     seats: &[Seats],
     criterion: impl Fn(Votes, Seats) -> Option<Quality>,
-) -> Option<usize> {
+    ctx: &mut Context,
+) -> Result<Option<usize>, AllocationError> {
+    check_lengths(votes, seats)?;
+
     let qualities = iter::zip(votes, seats.iter())
         .map(|(votes, seats)| {
-            if seats.has_candidates() && (!cfg!(feature = "undocumented") || *votes > Votes(0)) {
+            if seats.has_candidates() && (!ctx.rules.exclude_zero_votes || *votes > Votes(0)) {
                 criterion(*votes, *seats)
             } else {
                 None
@@ -23,39 +162,198 @@ pub fn allocation_winner<Quality: Ord>(
         })
         .collect::<Vec<_>>();
 
-    let max_quality = qualities.iter().max().unwrap().as_ref()?;
+    if ctx.observer.is_some() {
+        let standings = qualities
+            .iter()
+            .enumerate()
+            .filter_map(|(party, quality)| Some((party, quality.clone()?.into())))
+            .collect::<Vec<_>>();
+        ctx.notify(Event::Standings(standings));
+    }
+
+    let Some(Some(max_quality)) = qualities.iter().max() else {
+        return Ok(None);
+    };
 
     let awarded = iter::zip(qualities.iter(), 0..seats.len())
         .filter_map(|(quality, seat)| (quality.as_ref() == Some(max_quality)).then_some(seat))
         .collect::<Vec<_>>();
 
-    let seat = balloted(awarded)?;
+    if let [seat] = awarded[..] {
+        return Ok(Some(seat));
+    }
+
+    let Some(winner) = ctx.ballot.draw(&awarded) else {
+        return Err(AllocationError::LotNotDrawn { tied: awarded });
+    };
+    ctx.notify(Event::Lot {
+        tied: awarded,
+        winner,
+    });
 
-    Some(seat)
+    Ok(Some(winner))
 }
 
-// In OSV and Abacus, the absolute majority winner is calculated based on the elections results
-// as-if no list exhaustion exists. Based on the interpretation that article P9 precedes P10. This
-// forces us to calculate the election results twice. Because that's hard to do as it cuts across
-// potentially multiple rounds of rest seat awards, we solve this using a global.
-#[cfg(feature = "undocumented")]
-thread_local! {
-    static ABSOLUTE_MAJORITY_WINNER: std::cell::Cell<Option<Option<usize>>> = None.into();
+/// Check that the `votes` and `seats` describe the same number of parties.
+fn check_lengths(votes: &[Votes], seats: &[Seats]) -> Result<(), AllocationError> {
+    if votes.len() == seats.len() {
+        Ok(())
+    } else {
+        Err(AllocationError::LengthMismatch {
+            votes: votes.len(),
+            seats: seats.len(),
+        })
+    }
+}
+
+/// Add up numbers of votes, failing if the total does not fit in a `Count`.
+fn sum_votes(votes: impl IntoIterator<Item = Count>) -> Result<Count, AllocationError> {
+    votes
+        .into_iter()
+        .try_fold(0, Count::checked_add)
+        .ok_or(AllocationError::Overflow)
+}
+
+/// Check the input of a seat apportionment, and determine the total number of votes and of seats
+/// to apportion. The criteria of the methods compare products of a number of votes and a (small
+/// multiple of a) number of seats, so these must be representable.
+fn check_input(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &[Seats],
+) -> Result<(Count, Count), AllocationError> {
+    check_lengths(votes, seats)?;
+
+    let seat_count = total_seats.count();
+    if votes.is_empty() || seat_count == 0 {
+        return Err(AllocationError::EmptyInput);
+    }
+
+    let vote_count = sum_votes(votes.iter().map(|Votes(count)| *count))?;
+    if vote_count == 0 {
+        return Err(AllocationError::ZeroVotes);
+    }
+
+    seat_count
+        .checked_add(1)
+        .and_then(|n| n.checked_mul(4))
+        .and_then(|n| n.checked_mul(vote_count))
+        .ok_or(AllocationError::Overflow)?;
+
+    Ok((vote_count, seat_count))
+}
+
+/// The votes that a party needs to meet the threshold of the context, if it has one. The `seats`
+/// and the `available_seats` together are all the seats in the apportionment.
+fn threshold_votes(
+    votes: &[Votes],
+    seats: &[Seats],
+    available_seats: Seats,
+    ctx: &Context,
+) -> Result<Option<Fraction>, AllocationError> {
+    let Some(threshold) = ctx.threshold else {
+        return Ok(None);
+    };
+    let vote_count = sum_votes(votes.iter().map(|Votes(count)| *count))?;
+    let seat_count = seats.iter().map(|seat| seat.count()).sum::<Count>() + available_seats.count();
+    if seat_count == 0 {
+        return Ok(None);
+    }
+
+    threshold
+        .minimum(vote_count, seat_count)
+        .map(Some)
+        .ok_or(AllocationError::Overflow)
+}
+
+/// Fail if some of the seats that were to be apportioned could not be awarded.
+fn check_filled(total_seats: Seats) -> Result<(), AllocationError> {
+    match total_seats.count() {
+        0 => Ok(()),
+        remaining => Err(AllocationError::UnfillableSeats { remaining }),
+    }
+}
+
+/// A ballot that does not draw lots, but instead takes a predetermined path through all the
+/// possible lots, remembering how many choices there were at each drawing.
+struct Exploration {
+    path: Vec<usize>,
+    choices: Vec<usize>,
+}
+
+impl Exploration {
+    fn new() -> Self {
+        Exploration {
+            path: Vec::new(),
+            choices: Vec::new(),
+        }
+    }
+
+    /// Move on to the next unexplored path, if there is one
+    fn advance(&mut self) -> bool {
+        while let Some(last) = self.path.pop() {
+            if last + 1 < self.choices[self.path.len()] {
+                self.path.push(last + 1);
+                break;
+            }
+        }
+        self.choices.clear();
+        !self.path.is_empty()
+    }
+}
+
+impl Ballot for Exploration {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        let n = self.choices.len();
+        if n == self.path.len() {
+            self.path.push(0);
+        }
+        self.choices.push(tied.len());
+        Some(tied[self.path[n]])
+    }
+}
+
+/// Determine every outcome of an `allocation` that can result from drawing lots, together with
+/// the probability of that outcome. The `allocation` must be deterministic apart from the lots
+/// drawn using the provided ballot.
+pub fn possible_outcomes(
+    mut allocation: impl FnMut(&mut Context) -> Result<Vec<Seats>, AllocationError>,
+) -> Result<Vec<(Vec<Count>, Fraction)>, AllocationError> {
+    let mut outcomes = Vec::<(Vec<Count>, Fraction)>::new();
+    let mut ballot = Exploration::new();
+
+    loop {
+        let seats = allocation(&mut Context::new(&mut ballot))?;
+        let seats = seats.iter().map(|x| x.count()).collect::<Vec<_>>();
+        let chance = ballot
+            .choices
+            .iter()
+            .try_fold(frac(1, 1), |p, &n| p.checked_mul(frac(1, n as Count)))
+            .ok_or(AllocationError::Overflow)?;
+
+        match outcomes.iter_mut().find(|(outcome, _)| *outcome == seats) {
+            Some((_, probability)) => {
+                *probability = probability
+                    .checked_add(chance)
+                    .ok_or(AllocationError::Overflow)?
+            }
+            None => outcomes.push((seats, chance)),
+        }
+
+        if !ballot.advance() {
+            return Ok(outcomes);
+        }
+    }
 }
 
 /// This performs the correction stipulated in the Dutch law that a party that gets an
 /// absolute majority in votes also gets an absolute majority in seats.
 /// This step is criterion-agnostic.
 pub fn absolute_majority_winner(votes: &[Votes], seats: &[Seats]) -> Option<usize> {
-    #[cfg(feature = "undocumented")]
-    if let Some(Some(winner)) = ABSOLUTE_MAJORITY_WINNER.get() {
-        return seats[winner].has_candidates().then_some(winner);
-    }
-
     let total_votes = votes.iter().map(|Votes(count)| count).sum::<Count>();
     let total_seats = seats.iter().map(|count| count.count()).sum::<Count>();
 
-    let absolute_majority = |count, total| 2 * count > total;
+    let absolute_majority = |count, total| count > total - count;
 
     let (_, winner) = iter::zip(votes, 0..seats.len()).find(|(Votes(cur_vote), i)| {
         let cur_seat = seats[*i];
@@ -64,140 +362,210 @@ pub fn absolute_majority_winner(votes: &[Votes], seats: &[Seats]) -> Option<usiz
             && !absolute_majority(cur_seat.count(), total_seats)
     })?;
 
-    #[cfg(feature = "undocumented")]
-    if let Some(None) = ABSOLUTE_MAJORITY_WINNER.get() {
-        ABSOLUTE_MAJORITY_WINNER.set(Some(Some(winner)));
-    }
-
     Some(winner)
 }
 
-#[cfg(feature = "chatty")]
-fn debug_results(mut things: impl Iterator<Item: std::fmt::Display>) {
-    let Some(first) = things.next() else {
-        return;
-    };
-    eprint!("{first}");
-    for thing in things {
-        eprint!(", {thing}");
-    }
-    eprintln!();
-}
-
 /// Perform a seat apportionment based on the given method.
 /// It is a **requirement** that the `criterion` algorithm will always rank a party that is
 /// eligible for at least one more "seat" above a party that doesn't.
-pub fn allocate_seats<Quality: Ord + Clone + std::fmt::Display>(
+/// Seats for which no party is eligible remain in `available_seats`, as do all seats if no party
+/// meets the threshold of the context.
+pub fn allocate_seats<Quality: Ord + Clone + Into<Fraction>>(
     votes: &[Votes],
     seats: &mut [Seats],
     available_seats: &mut Seats,
     method: impl Fn(Votes, Seats) -> Option<Quality> + Copy,
-) -> Option<()> {
-    #[cfg(feature = "chatty")]
-    let mut debug_chat = {
-        let mut last_winners = seats.to_owned();
-        move |seats: &[Seats]| {
-            debug_results(
-                seats
-                    .iter()
-                    .zip(last_winners.iter())
-                    .enumerate()
-                    .filter_map(|(n, (x, y))| {
-                        (x != y).then_some(format!(
-                            "rest seat for {n} [{}]",
-                            method(votes[n], *y)
-                                .map(|x| x.to_string())
-                                .unwrap_or("#".to_string())
-                        ))
-                    }),
-            );
-
-            last_winners.copy_from_slice(seats);
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let minimum = threshold_votes(votes, seats, *available_seats, ctx)?;
+    let method = move |cur_vote: Votes, cur_seat| {
+        if minimum.is_none_or(|minimum| frac(cur_vote.0, 1) >= minimum) {
+            method(cur_vote, cur_seat)
+        } else {
+            None
         }
     };
 
     let mut last_seat = None;
 
     while available_seats.count() > 0 {
-        last_seat = allocation_winner(votes, seats, method);
-        seats[last_seat?].transfer(available_seats);
-
-        #[cfg(feature = "chatty")]
-        debug_chat(seats);
+        let Some(winner) = allocation_winner(votes, seats, method, ctx)? else {
+            return Ok(());
+        };
+        if ctx.observer.is_some() {
+            let quality = method(votes[winner], seats[winner]).unwrap();
+            ctx.notify(Event::RestSeat {
+                party: winner,
+                quality: quality.into(),
+            });
+        }
+        seats[winner].transfer(available_seats)?;
+        if !seats[winner].has_candidates() {
+            ctx.notify(Event::Exhausted { party: winner });
+        }
+        last_seat = Some(winner);
     }
 
-    if let Some(winner) = absolute_majority_winner(votes, seats) {
-        available_seats.transfer(&mut seats[last_seat.unwrap()]);
-        seats[winner].transfer(available_seats);
+    // the correction takes away the last seat that was awarded in this round, if there was one
+    if let Some(winner) = absolute_majority_winner(votes, seats)
+        && let Some(loser) = last_seat
+    {
+        available_seats.transfer(&mut seats[loser])?;
+        seats[winner].transfer(available_seats)?;
 
-        #[cfg(feature = "chatty")]
-        eprintln!("an absolute majority correction was performed");
-        #[cfg(feature = "chatty")]
-        debug_chat(seats);
+        ctx.notify(Event::MajorityCorrection {
+            from: loser,
+            to: winner,
+        });
     }
 
-    #[cfg(feature = "chatty")]
-    if let Some((n, q)) = votes
-        .iter()
-        .zip(seats as &[Seats])
-        .enumerate()
-        .map(|(n, (v, s))| (n, method(*v, *s)))
-        .max_by_key(|x| x.1.clone())
+    if ctx.observer.is_some()
+        && let Some((party, Some(quality))) = iter::zip(votes, seats.iter())
+            .enumerate()
+            .map(|(n, (v, s))| (n, s.has_candidates().then(|| method(*v, *s)).flatten()))
+            .rev()
+            .max_by(|(_, x), (_, y)| x.cmp(y))
     {
-        eprintln!(
-            "no rest seat for {n} [{}]",
-            q.map(|x| x.to_string()).unwrap_or("#".to_string())
-        );
+        ctx.notify(Event::RunnerUp {
+            party,
+            quality: quality.into(),
+        });
     }
 
-    Some(())
+    Ok(())
 }
 
 /// Perform a seat apportionment, only handing out full seats. This is not necessary but has the
 /// benefit that it is criterion-agnostic and faster than an explicit loop.
-#[allow(unused)]
-pub fn allocate_whole_seats(votes: &[Votes], seats: &mut [Seats], available_seats: &mut Seats) {
-    let vote_count = votes.iter().map(|Votes(count)| count).sum::<Count>();
+pub fn allocate_whole_seats(
+    votes: &[Votes],
+    seats: &mut [Seats],
+    available_seats: &mut Seats,
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    check_lengths(votes, seats)?;
+
+    let vote_count = sum_votes(votes.iter().map(|Votes(count)| *count))?;
     let seat_count = available_seats.count();
 
-    if vote_count == 0 {
-        return;
+    if vote_count == 0 || seat_count == 0 {
+        return Ok(());
     }
 
-    for (Votes(v), seat) in iter::zip(votes.iter(), seats.iter_mut()) {
-        for _ in 0..v * seat_count / vote_count {
+    allocate_quotas(
+        votes,
+        seats,
+        available_seats,
+        frac(vote_count, seat_count),
+        ctx,
+    )
+}
+
+/// Hand out a seat to every party for every whole `quota` of votes it received.
+fn allocate_quotas(
+    votes: &[Votes],
+    seats: &mut [Seats],
+    available_seats: &mut Seats,
+    quota: Fraction,
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let minimum = threshold_votes(votes, seats, *available_seats, ctx)?;
+    let whole_seats = votes
+        .iter()
+        .map(|Votes(v)| {
+            if minimum.is_some_and(|minimum| frac(*v, 1) < minimum) {
+                return Ok(0);
+            }
+            v.checked_mul(quota.denominator)
+                .map(|v| v / quota.numerator)
+                .ok_or(AllocationError::Overflow)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let whole_count = whole_seats.iter().sum::<Count>();
+    if whole_count > available_seats.count() {
+        return Err(AllocationError::QuotaTooSmall {
+            excess: whole_count - available_seats.count(),
+        });
+    }
+
+    ctx.notify(Event::Quota(quota));
+
+    for (party, (whole, seat)) in iter::zip(whole_seats, seats.iter_mut()).enumerate() {
+        for _ in 0..whole {
             if seat.count() < seat.limit {
-                seat.transfer(available_seats)
+                seat.transfer(available_seats)?;
             }
         }
+        if seat.count() > 0 {
+            ctx.notify(Event::WholeSeats {
+                party,
+                seats: seat.count(),
+            });
+        }
+        if !seat.has_candidates() {
+            ctx.notify(Event::Exhausted { party });
+        }
     }
 
-    #[cfg(feature = "chatty")]
-    {
-        eprintln!("threshold: {}", frac(vote_count, seat_count));
-        eprintln!("whole seats:");
-        debug_results(
-            seats
-                .iter()
-                .enumerate()
-                .filter_map(|(n, x)| (x.count() > 0).then_some(format!("{n}: {x}"))),
-        );
-        eprintln!("rest seats ({})", available_seats.count());
-    }
+    Ok(())
 }
 
 /// Perform a seat apportionment based on the D'Hondt method.
 /// This system is currently used in the Netherlands for regional councils least 19 seats or more.
-pub fn allocate_per_average(mut total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
-    #[cfg(feature = "whole-seat-opt")]
-    allocate_whole_seats(votes, seats, &mut total_seats);
+pub fn allocate_per_average(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    allocate_per_divisor(DivisorMethod::DHondt, total_seats, votes, seats, ctx)
+}
+
+/// Perform a seat apportionment based on the highest averages of the given divisor `method`.
+/// Candidate limits and the absolute majority correction apply as they do for D'Hondt.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let mut ballot = LowestListNumber;
+/// let mut ctx = Context::new(&mut ballot);
+/// let votes = [Votes(4700), Votes(2800), Votes(1700), Votes(800)];
+///
+/// for (method, result) in [
+///     (DivisorMethod::DHondt, [4, 2, 1, 0]),
+///     (DivisorMethod::SainteLague, [3, 2, 1, 1]),
+///     (DivisorMethod::ModifiedSainteLague, [4, 2, 1, 0]),
+///     (DivisorMethod::HuntingtonHill, [3, 2, 1, 1]),
+/// ] {
+///     let mut seats = [Seats::unlimited(); 4];
+///     allocate_per_divisor(method, Seats::filled(7), &votes, &mut seats, &mut ctx).unwrap();
+///     assert_eq!(seats.map(|x| x.count()), result, "{method}");
+/// }
+/// ```
+pub fn allocate_per_divisor(
+    method: DivisorMethod,
+    mut total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    check_input(total_seats, votes, seats)?;
+
+    // of these methods, only D'Hondt guarantees every party its whole seats
+    if method == DivisorMethod::DHondt {
+        allocate_whole_seats(votes, seats, &mut total_seats, ctx)?;
+    }
 
     allocate_seats(
         votes,
         seats,
         &mut total_seats,
-        |Votes(cur_vote), cur_seat| Some(frac(cur_vote, cur_seat.count() + 1)),
-    );
+        |cur_vote, cur_seat| Some(method.quotient(cur_vote, cur_seat)),
+        ctx,
+    )?;
+
+    check_filled(total_seats)
 }
 
 /// Perform a seat apportionment based on the Hamilton method, with a
@@ -205,225 +573,661 @@ pub fn allocate_per_average(mut total_seats: Seats, votes: &[Votes], seats: &mut
 /// If seats remain after that, apportion the remainder of seats using D'Hondt, with
 /// parties again only receiving a maximum of one additional seat.
 /// This system is currently used in the Netherlands for bodies of less than 19 seats.
-pub fn allocate_per_surplus(mut total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
-    let vote_count = votes.iter().map(|Votes(count)| count).sum::<Count>();
-    let seat_count = total_seats.count();
+pub fn allocate_per_surplus(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    surplus_method(QuotaMethod::DUTCH, total_seats, votes, seats, ctx)
+}
 
-    #[cfg(feature = "whole-seat-opt")]
-    allocate_whole_seats(votes, seats, &mut total_seats);
+/// Perform a seat apportionment as `allocate_per_surplus` did until 2017: the seats that remain
+/// after the largest surpluses are apportioned by largest averages, without restricting the
+/// number of seats a party can receive in that round.
+pub fn allocate_per_surplus_before_2017(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    surplus_method(
+        QuotaMethod::DUTCH_BEFORE_2017,
+        total_seats,
+        votes,
+        seats,
+        ctx,
+    )
+}
 
-    let has_surplus =
-        |cur_vote, cur_seat| frac(cur_vote, 1) >= frac(cur_seat * vote_count, seat_count);
+/// The apportionment for a Dutch body of less than 19 seats by the quota `method`, including the
+/// absolute majority correction
+fn surplus_method(
+    method: QuotaMethod,
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let majority_winner = if ctx.rules.majority_before_exhaustion {
+        prefetch_majority_winner(method, total_seats, votes, seats, ctx)?
+    } else {
+        None
+    };
+
+    surplus_rounds(method, total_seats, votes, seats, majority_winner, ctx)
+}
+
+/// In OSV and Abacus, the absolute majority winner is calculated based on the election results
+/// as-if no list exhaustion exists, based on the interpretation that article P9 precedes P10.
+/// This runs a shadow apportionment with unlimited lists to determine who that is, if the
+/// correction could be affected by list exhaustion at all. The lots of the shadow apportionment
+/// are not drawn with the ballot of `ctx`, unless the winner depends on them.
+fn prefetch_majority_winner(
+    method: QuotaMethod,
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &[Seats],
+    ctx: &mut Context,
+) -> Result<Option<usize>, AllocationError> {
+    let (vote_count, _) = check_input(total_seats, votes, seats)?;
+    let absolute_majority_exists = votes.iter().any(|&Votes(vote)| vote > vote_count - vote);
+    let lists_limited = seats.iter().any(|seat| seat.limit < Count::MAX);
+
+    if !absolute_majority_exists || !lists_limited {
+        return Ok(None);
+    }
+
+    // the shadow apportionment is not part of the trace
+    let (rules, threshold) = (ctx.rules, ctx.threshold);
+    let shadow = |ballot: &mut dyn Ballot| {
+        let mut events = Vec::new();
+        surplus_rounds(
+            method,
+            total_seats,
+            votes,
+            &mut vec![Seats::unlimited(); seats.len()],
+            None,
+            &mut Context {
+                threshold,
+                ..Context::observed(ballot, &mut events).with_rules(rules)
+            },
+        )?;
+
+        Ok(events.into_iter().find_map(|event| match event {
+            Event::MajorityCorrection { to, .. } => Some(to),
+            _ => None,
+        }))
+    };
+
+    let mut exploration = Exploration::new();
+    let winner = shadow(&mut exploration)?;
+    while exploration.advance() {
+        if shadow(&mut exploration)? != winner {
+            // the correction depends on drawing lots, which therefore have to be drawn
+            return shadow(&mut *ctx.ballot);
+        }
+    }
+    Ok(winner)
+}
+
+/// The rounds of `allocate_per_surplus`, where the `majority_winner` (if any) receives the seat
+/// of the absolute majority correction before the surpluses are considered.
+fn surplus_rounds(
+    method: QuotaMethod,
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    majority_winner: Option<usize>,
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let mut total_seats = quota_rounds(method, total_seats, votes, seats, majority_winner, ctx)?;
+
+    // In the Kiesraad specification, an undocumented-by-law third round of unrestricted
+    // averages is stipulated as a last-ditch effort, in preference to leaving seats
+    // unoccupied. This has never happened in practice.
+    if ctx.rules.unrestricted_averages && total_seats.count() > 0 {
+        ctx.notify(Event::Round(Round::UnrestrictedAverages));
+        allocate_seats(
+            votes,
+            seats,
+            &mut total_seats,
+            |Votes(vote), cur_seat| Some(frac(vote, cur_seat.count() + 1)),
+            ctx,
+        )?;
+    }
+
+    check_filled(total_seats)
+}
+
+/// Perform a seat apportionment by largest surpluses, as described by the quota `method`.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let mut ballot = LowestListNumber;
+/// let mut ctx = Context::new(&mut ballot);
+/// let votes = [Votes(4700), Votes(2800), Votes(1700), Votes(800)];
+///
+/// for (quota, result) in [
+///     (Quota::Hare, [3, 2, 1, 1]),
+///     (Quota::Droop, [4, 2, 1, 0]),
+///     (Quota::Imperiali, [4, 2, 1, 0]),
+/// ] {
+///     let method = QuotaMethod {
+///         quota,
+///         threshold: None,
+///         surplus_cap: 1,
+///         fallback: Fallback::Unoccupied,
+///     };
+///     let mut seats = [Seats::unlimited(); 4];
+///     allocate_per_quota(method, Seats::filled(7), &votes, &mut seats, &mut ctx).unwrap();
+///     assert_eq!(seats.map(|x| x.count()), result, "{quota}");
+/// }
+/// ```
+pub fn allocate_per_quota(
+    method: QuotaMethod,
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let total_seats = quota_rounds(method, total_seats, votes, seats, None, ctx)?;
+
+    check_filled(total_seats)
+}
+
+/// The rounds of `allocate_per_quota`, where the `majority_winner` (if any) receives the seat of
+/// the absolute majority correction before the surpluses are considered. Returns the seats that
+/// remain unoccupied.
+fn quota_rounds(
+    method: QuotaMethod,
+    mut total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    majority_winner: Option<usize>,
+    ctx: &mut Context,
+) -> Result<Seats, AllocationError> {
+    let (vote_count, seat_count) = check_input(total_seats, votes, seats)?;
+    let quota = method.quota.of(vote_count, seat_count)?;
+
+    let threshold = match method.threshold {
+        Some(SurplusThreshold::Votes(share) | SurplusThreshold::VotesPerSeat(share)) => {
+            share.checked_mul(quota).ok_or(AllocationError::Overflow)?
+        }
+        None => frac(0, 1),
+    };
+
+    allocate_quotas(votes, seats, &mut total_seats, quota, ctx)?;
 
     // The "prefetched" majority correction does not influence the determination of "surplus", but
     // it does influence the "average votes per seat", which is very inconvenient.
-    #[cfg(feature = "undocumented")]
-    let has_surplus: Box<dyn Fn(_, _) -> bool> = {
-        if let Some(Some(winner)) = ABSOLUTE_MAJORITY_WINNER.get()
-            && seats[winner].has_candidates()
-        {
-            #[cfg(feature = "chatty")]
-            eprintln!("awarding a majority bonus seat to {winner}");
-            seats[winner].transfer(&mut total_seats);
-            ABSOLUTE_MAJORITY_WINNER.set(None);
-
-            let Votes(majority_votes) = votes[winner];
-
-            Box::new(move |cur_vote, cur_seat| {
-                if cur_vote == majority_votes {
-                    has_surplus(cur_vote, cur_seat - 1)
-                } else {
-                    has_surplus(cur_vote, cur_seat)
-                }
-            })
+    let mut majority_votes = None;
+    if let Some(winner) = majority_winner
+        && seats[winner].has_candidates()
+    {
+        seats[winner].transfer(&mut total_seats)?;
+        ctx.notify(Event::MajorityBonus { party: winner });
+        majority_votes = Some(votes[winner].0);
+    }
+
+    // the seats of a party that are paid for by its votes
+    let counted = |Votes(cur_vote), cur_seat: Seats| {
+        if Some(cur_vote) == majority_votes {
+            cur_seat.count() - 1
         } else {
-            Box::new(has_surplus)
+            cur_seat.count()
         }
     };
+    let whole_quotas = |cur_vote: Count| cur_vote * quota.denominator / quota.numerator;
+    let surplus = |cur_vote: Count, cur_seat: Count| {
+        (cur_vote * quota.denominator).checked_sub(cur_seat * quota.numerator)
+    };
+    let qualifies = |cur_vote: Count| match method.threshold {
+        Some(SurplusThreshold::Votes(_)) => frac(cur_vote, 1) >= threshold,
+        Some(SurplusThreshold::VotesPerSeat(_)) => {
+            frac(cur_vote, whole_quotas(cur_vote) + 1) >= threshold
+        }
+        None => true,
+    };
 
-    allocate_seats(
-        votes,
-        seats,
-        &mut total_seats,
-        |Votes(cur_vote), cur_seat| {
-            let cur_seat = cur_seat.count();
-            (has_surplus(cur_vote, cur_seat)
-                && frac(cur_vote, 1) >= frac(3 * vote_count, 4 * seat_count))
-            .then(|| {
-                if cfg!(feature = "whole-seat-opt") {
-                    cur_vote * seat_count % vote_count
-                } else if cfg!(not(feature = "undocumented")) {
-                    cur_vote * seat_count - cur_seat * vote_count
-                } else {
-                    unreachable!("'undocumented' requires 'whole-seat-opt'")
-                }
-            })
-        },
-    );
-
-    if total_seats.count() > 0 {
-        #[cfg(feature = "chatty")]
-        eprintln!("continuing by averages");
+    for round in 1..=method.surplus_cap {
+        if round > 1 && total_seats.count() == 0 {
+            break;
+        }
+        ctx.notify(Event::Round(match round {
+            1 => Round::Surplus,
+            2 => Round::SecondSurplus,
+            _ => Round::FurtherSurplus(round),
+        }));
         allocate_seats(
             votes,
             seats,
             &mut total_seats,
-            |Votes(cur_vote), cur_seat| {
-                let cur_seat = cur_seat.count();
-                if frac(cur_vote, 1) >= frac(3 * vote_count, 4 * seat_count) {
-                    has_surplus(cur_vote, cur_seat - 1)
+            |cur_vote, cur_seat| {
+                // the surplus over the seats that the party had before its previous surplus seats
+                let cur_seat = counted(cur_vote, cur_seat).checked_sub(round - 1)?;
+                let Votes(cur_vote) = cur_vote;
+                if qualifies(cur_vote) {
+                    surplus(cur_vote, cur_seat)
                 } else {
-                    has_surplus(cur_vote, cur_seat)
+                    None
                 }
-                .then_some(frac(cur_vote, cur_seat + 1))
             },
-        );
+            ctx,
+        )?;
     }
 
-    // In the Kiesraad specification, an undocumented-by-law third round of unrestricted
-    // averages is stipulated as a last-ditch effort, in preference to leaving seats
-    // unoccupied. This has never happened in practice.
-    #[cfg(feature = "undocumented")]
     if total_seats.count() > 0 {
-        #[cfg(feature = "chatty")]
-        eprintln!("continuing by unrestricted averages");
-        allocate_per_average(total_seats, votes, seats);
+        match method.fallback {
+            Fallback::Unoccupied => {}
+            Fallback::UnrestrictedAverages => {
+                ctx.notify(Event::Round(Round::Averages));
+                allocate_seats(
+                    votes,
+                    seats,
+                    &mut total_seats,
+                    |Votes(vote), cur_seat| Some(frac(vote, cur_seat.count() + 1)),
+                    ctx,
+                )?;
+            }
+            Fallback::Averages => {
+                ctx.notify(Event::Round(Round::Averages));
+                allocate_seats(
+                    votes,
+                    seats,
+                    &mut total_seats,
+                    |cur_vote, cur_seat| {
+                        let Votes(vote) = cur_vote;
+                        let limit = whole_quotas(vote)
+                            + if qualifies(vote) {
+                                method.surplus_cap + 1
+                            } else {
+                                1
+                            };
+                        (counted(cur_vote, cur_seat) < limit)
+                            .then_some(frac(vote, cur_seat.count() + 1))
+                    },
+                    ctx,
+                )?;
+            }
+            Fallback::BelowThreshold => {
+                ctx.notify(Event::Round(Round::BelowThreshold));
+                allocate_seats(
+                    votes,
+                    seats,
+                    &mut total_seats,
+                    |cur_vote, cur_seat| {
+                        let cur_seat = counted(cur_vote, cur_seat);
+                        let Votes(cur_vote) = cur_vote;
+                        if qualifies(cur_vote) {
+                            None
+                        } else {
+                            surplus(cur_vote, cur_seat)
+                        }
+                    },
+                    ctx,
+                )?;
+            }
+        }
     }
+
+    Ok(total_seats)
 }
 
 /// Perform a seat apportionment, selecting D'Hondt or modified-Hamilton
 /// based on the number of seats, as Dutch law does for bodies.
-pub fn allocate(total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
+pub fn allocate(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
     if total_seats.count() >= 19 {
-        allocate_per_average(total_seats, votes, seats);
+        allocate_per_average(total_seats, votes, seats, ctx)
     } else {
-        #[cfg(feature = "undocumented")]
-        let _guard = prefetch_majority_correction(allocate_per_surplus, total_seats, votes, seats);
-
-        allocate_per_surplus(total_seats, votes, seats);
+        allocate_per_surplus(total_seats, votes, seats, ctx)
     }
 }
 
 /// Perform a seat apportionment using D'Hondt's method and a voting threshold
 /// of one whole seat, as used in Dutch national elections (parliament and European Parliament)
-pub fn allocate_national(mut total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
-    let vote_count = votes.iter().map(|Votes(count)| count).sum::<Count>();
-    let seat_count = total_seats.count();
+pub fn allocate_national(
+    mut total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let (vote_count, seat_count) = check_input(total_seats, votes, seats)?;
 
-    #[cfg(feature = "whole-seat-opt")]
-    allocate_whole_seats(votes, seats, &mut total_seats);
+    allocate_whole_seats(votes, seats, &mut total_seats, ctx)?;
 
     allocate_seats(
         votes,
         seats,
         &mut total_seats,
-        |Votes(cur_vote), cur_seat| {
-            (frac(cur_vote, 1) >= frac(vote_count, seat_count))
-                .then_some(frac(cur_vote, cur_seat.count() + 1))
+        |cur_vote, cur_seat| {
+            (frac(cur_vote.0, 1) >= frac(vote_count, seat_count))
+                .then(|| DivisorMethod::DHondt.quotient(cur_vote, cur_seat))
         },
-    );
+        ctx,
+    )?;
+
+    check_filled(total_seats)
 }
 
-/// Perform an "optimistic" majority correction on the results before considering list exhaustion.
-#[cfg(feature = "undocumented")]
-#[must_use]
-fn prefetch_majority_correction<'a>(
-    alloc: fn(Seats, &[Votes], &mut [Seats]),
+/// Perform a seat apportionment using D'Hondt's method and a voting threshold of one whole seat,
+/// where lists that have entered into a "lijstcombinatie" compete for seats as a single list.
+/// This was the system for the Tweede Kamer from 1977 until 2017. The `combinations` contain the
+/// indices of the lists that were combined; lists that do not meet the voting threshold on their
+/// own do not take part in a combination.
+pub fn allocate_combined(
     total_seats: Seats,
     votes: &[Votes],
-    seats: &[Seats],
-) -> impl Drop + 'a {
-    use std::cmp::{max, min};
-    let mut max_vote = 0;
-    let mut sum_vote = 0;
-    let mut min_limit = Count::MAX;
-    for (&Votes(vote), seat) in iter::zip(votes, seats) {
-        max_vote = max(max_vote, vote);
-        min_limit = min(min_limit, seat.limit);
-        sum_vote += vote;
-    }
+    seats: &mut [Seats],
+    combinations: &[Vec<usize>],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let (vote_count, seat_count) = check_input(total_seats, votes, seats)?;
+    check_indices(combinations, votes.len())?;
 
-    let absolute_majority_exists = 2 * max_vote > sum_vote;
+    let minimum = threshold_votes(votes, seats, total_seats, ctx)?;
+    let meets_threshold = |i: usize| {
+        frac(votes[i].0, 1) >= frac(vote_count, seat_count)
+            && minimum.is_none_or(|minimum| frac(votes[i].0, 1) >= minimum)
+    };
 
-    struct Guard;
-    impl Drop for Guard {
-        fn drop(&mut self) {
-            ABSOLUTE_MAJORITY_WINNER.set(None);
+    allocate_combinations(
+        total_seats,
+        votes,
+        seats,
+        combinations,
+        meets_threshold,
+        allocate_national,
+        ctx,
+    )
+}
+
+/// Perform a seat apportionment for a council with list combinations, as was possible until 2017:
+/// a combination takes part as a single list in `allocate_per_average` or (in a body of less than
+/// 19 seats) `allocate_per_surplus_before_2017`, after which its seats are divided over its members
+/// by largest surpluses.
+pub fn allocate_combined_before_2017(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    combinations: &[Vec<usize>],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    check_input(total_seats, votes, seats)?;
+    check_indices(combinations, votes.len())?;
+
+    let apportion = if total_seats.count() >= 19 {
+        allocate_per_average
+    } else {
+        allocate_per_surplus_before_2017
+    };
+    allocate_combinations(
+        total_seats,
+        votes,
+        seats,
+        combinations,
+        |_| true,
+        apportion,
+        ctx,
+    )
+}
+
+/// A function that performs a seat apportionment, such as `allocate_per_average`
+type Apportionment = fn(Seats, &[Votes], &mut [Seats], &mut Context) -> Result<(), AllocationError>;
+
+/// Apportion the seats over the combinations of lists by the method `apportion`, where only the
+/// lists that are `eligible` share in the seats of their combination, and then divide the seats of
+/// every combination over its members
+fn allocate_combinations(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    combinations: &[Vec<usize>],
+    eligible: impl Fn(usize) -> bool,
+    apportion: Apportionment,
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let mut groups = combinations
+        .iter()
+        .map(|members| {
+            members
+                .iter()
+                .copied()
+                .filter(|&i| eligible(i))
+                .collect::<Vec<_>>()
+        })
+        .filter(|members| !members.is_empty())
+        .collect::<Vec<_>>();
+
+    for i in 0..votes.len() {
+        if !groups.iter().flatten().any(|&j| i == j) {
+            groups.push(vec![i]);
         }
     }
 
-    ABSOLUTE_MAJORITY_WINNER.set(Some(None));
+    let group_votes = groups
+        .iter()
+        .map(|members| Votes(members.iter().map(|&i| votes[i].0).sum()))
+        .collect::<Vec<_>>();
+    let mut group_seats = groups
+        .iter()
+        .map(|members| {
+            Seats::limited(
+                members
+                    .iter()
+                    .map(|&i| seats[i].limit)
+                    .fold(0, Count::saturating_add),
+            )
+        })
+        .collect::<Vec<_>>();
 
-    if absolute_majority_exists && min_limit < Count::MAX {
-        // run a shadow allocation with unlimited party lists to load the majority winner
-        // in the thread_local Cell as a side effect
-        let seats = &mut vec![Seats::unlimited(); seats.len()];
-        alloc(total_seats, votes, seats);
-    }
+    // a combination is represented by its first member in the trace
+    let representatives = groups.iter().map(|members| members[0]).collect::<Vec<_>>();
+    ctx.restricted(&representatives, |ctx| {
+        apportion(total_seats, &group_votes, &mut group_seats, ctx)
+    })?;
 
-    Guard
+    allocate_within_groups(
+        &groups,
+        &group_seats,
+        votes,
+        seats,
+        |members, seats| Round::Combination { members, seats },
+        ctx,
+    )
 }
 
-/// Perform a seat apportionment using the method that seems to have been in place from 1925 until
-/// the introduction of D'Hondt method, at least for the national election. It is the single-seat
-/// Hamilton method. And an extra requirement that a party always needs to have 75% of a whole seat
-/// *on average*, which acts like a quite ingenious voting threshold.
-/// If seats remain, they are then apportioned by the "single-additional seat D'Hondt" method.
-pub fn allocate_bongaerts(mut total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
-    let vote_count = votes.iter().map(|Votes(count)| count).sum::<Count>();
-    let seat_count = total_seats.count();
+/// Check that every group of lists refers to existing lists.
+fn check_indices(groups: &[Vec<usize>], lists: usize) -> Result<(), AllocationError> {
+    match groups.iter().flatten().find(|&&i| i >= lists) {
+        Some(&i) => Err(AllocationError::UnknownList(i)),
+        None => Ok(()),
+    }
+}
 
-    let has_surplus =
-        |cur_vote, cur_seat| frac(cur_vote, 1) >= frac(cur_seat * vote_count, seat_count);
+/// Perform a seat apportionment for the Tweede Kamer where parties submitted different lists in
+/// the kieskringen. The seats of every party are determined using `allocate_national`, and then
+/// divided over its lists by largest surpluses on their total number of votes.
+/// `votes[k][i]` is the number of votes for list `i` in kieskring `k` (zero if the list did not
+/// take part in that kieskring); identical lists that were submitted in several kieskringen are a
+/// single list. The `parties` contain the indices of the lists of each party. The `seats` of a list
+/// can be limited to the number of candidates on it, and the limit of a party is the total of the
+/// limits of its lists.
+pub fn allocate_party_lists(
+    total_seats: Seats,
+    votes: &[Vec<Votes>],
+    seats: &mut [Seats],
+    parties: &[Vec<usize>],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    if let Some(kieskring) = votes
+        .iter()
+        .find(|kieskring| kieskring.len() != seats.len())
+    {
+        return Err(AllocationError::LengthMismatch {
+            votes: kieskring.len(),
+            seats: seats.len(),
+        });
+    }
+    if parties.iter().any(|lists| lists.is_empty()) {
+        return Err(AllocationError::EmptyInput);
+    }
+    check_indices(parties, seats.len())?;
 
-    #[cfg(feature = "whole-seat-opt")]
-    allocate_whole_seats(votes, seats, &mut total_seats);
+    let list_votes = (0..seats.len())
+        .map(|i| sum_votes(votes.iter().map(|kieskring| kieskring[i].0)).map(Votes))
+        .collect::<Result<Vec<_>, _>>()?;
+    check_input(total_seats, &list_votes, seats)?;
 
-    allocate_seats(
-        votes,
+    let party_votes = parties
+        .iter()
+        .map(|lists| Votes(lists.iter().map(|&i| list_votes[i].0).sum()))
+        .collect::<Vec<_>>();
+    let mut party_seats = parties
+        .iter()
+        .map(|lists| {
+            Seats::limited(
+                lists
+                    .iter()
+                    .map(|&i| seats[i].limit)
+                    .fold(0, Count::saturating_add),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // a party is represented by its first list in the trace
+    let representatives = parties.iter().map(|lists| lists[0]).collect::<Vec<_>>();
+    ctx.restricted(&representatives, |ctx| {
+        allocate_national(total_seats, &party_votes, &mut party_seats, ctx)
+    })?;
+
+    allocate_within_groups(
+        parties,
+        &party_seats,
+        &list_votes,
         seats,
-        &mut total_seats,
-        |Votes(cur_vote), cur_seat| {
-            let cur_seat = cur_seat.count();
-            // proposed by bongaerts in 1922 and adopted in law in 1925
-            (has_surplus(cur_vote, cur_seat)
-                && frac(cur_vote, cur_seat + 1) >= frac(3 * vote_count, 4 * seat_count))
-            .then(|| cur_vote * seat_count - cur_seat * vote_count)
-        },
-    );
+        |lists, seats| Round::PartyLists { lists, seats },
+        ctx,
+    )
+}
 
-    if total_seats.count() > 0 {
-        #[cfg(feature = "chatty")]
-        eprintln!("continuing by averages");
-        allocate_seats(
+/// Divide the seats awarded to every group of lists over its members by largest surpluses.
+fn allocate_within_groups(
+    groups: &[Vec<usize>],
+    group_seats: &[Seats],
+    votes: &[Votes],
+    seats: &mut [Seats],
+    round: impl Fn(Vec<usize>, Count) -> Round,
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    for (members, combined_seats) in iter::zip(groups, group_seats) {
+        let member_votes = members.iter().map(|&i| votes[i]).collect::<Vec<_>>();
+        let mut member_seats = members.iter().map(|&i| seats[i]).collect::<Vec<_>>();
+
+        if members.len() > 1 {
+            ctx.notify(Event::Round(round(members.clone(), combined_seats.count())));
+        }
+
+        ctx.restricted(members, |ctx| {
+            allocate_within_combination(
+                Seats::filled(combined_seats.count()),
+                &member_votes,
+                &mut member_seats,
+                ctx,
+            )
+        })?;
+
+        for (&i, seat) in iter::zip(members, member_seats) {
+            seats[i] = seat;
+        }
+    }
+
+    Ok(())
+}
+
+/// Divide the seats awarded to a list combination (or a party) over its member lists by largest
+/// surpluses. No absolute majority correction takes place between the members of a combination.
+fn allocate_within_combination(
+    mut total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    if total_seats.count() == 0 {
+        return Ok(());
+    }
+    let (vote_count, seat_count) = check_input(total_seats, votes, seats)?;
+
+    let has_surplus =
+        |cur_vote, cur_seat| frac(cur_vote, 1) >= frac(cur_seat * vote_count, seat_count);
+
+    while total_seats.count() > 0 {
+        let Some(winner) = allocation_winner(
             votes,
             seats,
-            &mut total_seats,
-            //bongaerts in 1922 seems to have proposed this instead (straight saint-laguë method)
-            //|Votes(cur_vote), cur_seat| Some(frac(2*cur_vote, 2*cur_seat.count() + 1)),
             |Votes(cur_vote), cur_seat| {
                 let cur_seat = cur_seat.count();
-                if cur_seat > 0 && frac(cur_vote, cur_seat) >= frac(3 * vote_count, 4 * seat_count)
-                {
-                    has_surplus(cur_vote, cur_seat - 1)
-                } else {
-                    has_surplus(cur_vote, cur_seat)
-                }
-                .then_some(frac(cur_vote, cur_seat + 1))
+                has_surplus(cur_vote, cur_seat)
+                    .then(|| cur_vote * seat_count - cur_seat * vote_count)
             },
-        );
+            ctx,
+        )?
+        else {
+            break;
+        };
+        if ctx.observer.is_some() {
+            let Votes(cur_vote) = votes[winner];
+            let cur_seat = seats[winner].count();
+            ctx.notify(Event::RestSeat {
+                party: winner,
+                quality: (cur_vote * seat_count - cur_seat * vote_count).into(),
+            });
+        }
+        seats[winner].transfer(&mut total_seats)?;
     }
+
+    check_filled(total_seats)
+}
+
+/// Perform a seat apportionment using the method that seems to have been in place from 1925 until
+/// the introduction of D'Hondt method, at least for the national election. It is the single-seat
+/// Hamilton method. And an extra requirement that a party always needs to have 75% of a whole seat
+/// *on average*, which acts like a quite ingenious voting threshold.
+/// If seats remain, they are then apportioned by the "single-additional seat D'Hondt" method.
+pub fn allocate_bongaerts(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    allocate_per_quota(QuotaMethod::BONGAERTS, total_seats, votes, seats, ctx)
 }
 
 /// The seat apportionment used in the very first election with proportional representation.
-pub fn allocate_1918(total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
-    allocate_archaic(frac(1, 2), total_seats, votes, seats);
+pub fn allocate_1918(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    allocate_per_quota(QuotaMethod::ARCHAIC_1918, total_seats, votes, seats, ctx)
 }
 
 /// The seat apportionment used in the strange 1922 election.
 /// This has an increased voting threshold of 75% instead of the original 50%.
-pub fn allocate_1922(total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
-    allocate_archaic(frac(3, 4), total_seats, votes, seats);
+pub fn allocate_1922(
+    total_seats: Seats,
+    votes: &[Votes],
+    seats: &mut [Seats],
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    allocate_per_quota(QuotaMethod::ARCHAIC_1922, total_seats, votes, seats, ctx)
 }
 
 /// Perform a seat apportionment using the method that seems to have been selected around 1916
@@ -438,44 +1242,205 @@ pub fn allocate_1922(total_seats: Seats, votes: &[Votes], seats: &mut [Seats]) {
 ///
 /// This silly system was abandoned in 1922.
 pub fn allocate_archaic(
-    mut threshold: Fraction,
-    mut total_seats: Seats,
+    threshold: Fraction,
+    total_seats: Seats,
     votes: &[Votes],
     seats: &mut [Seats],
-) {
-    let vote_count = votes.iter().map(|Votes(count)| count).sum::<Count>();
-    let seat_count = total_seats.count();
+    ctx: &mut Context,
+) -> Result<(), AllocationError> {
+    let method = QuotaMethod {
+        threshold: Some(SurplusThreshold::Votes(threshold)),
+        ..QuotaMethod::ARCHAIC_1918
+    };
+    allocate_per_quota(method, total_seats, votes, seats, ctx)
+}
 
-    threshold.numerator *= vote_count;
-    threshold.denominator *= seat_count;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let has_surplus =
-        |cur_vote, cur_seat| frac(cur_vote, 1) >= frac(cur_seat * vote_count, seat_count);
+    type Allocation =
+        fn(Seats, &[Votes], &mut [Seats], &mut Context) -> Result<(), AllocationError>;
+
+    const ALLOCATIONS: [(&str, Allocation); 12] = [
+        ("allocate", allocate),
+        ("allocate_national", allocate_national),
+        ("allocate_per_average", allocate_per_average),
+        ("allocate_per_surplus", allocate_per_surplus),
+        (
+            "allocate_per_surplus_before_2017",
+            allocate_per_surplus_before_2017,
+        ),
+        ("allocate_bongaerts", allocate_bongaerts),
+        ("allocate_1918", allocate_1918),
+        ("allocate_1922", allocate_1922),
+        ("allocate_per_divisor", |total_seats, votes, seats, ctx| {
+            allocate_per_divisor(DivisorMethod::SainteLague, total_seats, votes, seats, ctx)
+        }),
+        ("allocate_archaic", |total_seats, votes, seats, ctx| {
+            allocate_archaic(frac(1, 2), total_seats, votes, seats, ctx)
+        }),
+        ("allocate_combined", |total_seats, votes, seats, ctx| {
+            allocate_combined(total_seats, votes, seats, &[vec![0, 1]], ctx)
+        }),
+        (
+            "allocate_combined_before_2017",
+            |total_seats, votes, seats, ctx| {
+                allocate_combined_before_2017(total_seats, votes, seats, &[vec![0, 1]], ctx)
+            },
+        ),
+    ];
 
-    #[cfg(feature = "whole-seat-opt")]
-    allocate_whole_seats(votes, seats, &mut total_seats);
+    fn run(
+        allocation: Allocation,
+        seat_count: Count,
+        votes: &[Count],
+    ) -> Result<Vec<Count>, AllocationError> {
+        let mut ballot = LowestListNumber;
+        let mut ctx = Context::new(&mut ballot);
+        let votes = votes.iter().map(|&x| Votes(x)).collect::<Vec<_>>();
+        let mut seats = vec![Seats::unlimited(); votes.len()];
+        allocation(Seats::filled(seat_count), &votes, &mut seats, &mut ctx)?;
+        Ok(seats.iter().map(|x| x.count()).collect())
+    }
 
-    let mut round = |num, meet_threshold| {
-        if total_seats.count() > 0 {
-            #[cfg(feature = "chatty")]
-            if num > 0 {
-                eprintln!("entering second round of surplus apportionment");
-            }
-            allocate_seats(
-                votes,
-                seats,
-                &mut total_seats,
-                |Votes(cur_vote), cur_seat| {
-                    (((frac(cur_vote, 1) >= threshold) == meet_threshold)
-                        && has_surplus(cur_vote, cur_seat.count() - num))
-                    .then(|| cur_vote * seat_count - (cur_seat.count() - num) * vote_count)
-                },
-            );
+    #[test]
+    fn large_proportional_votes() {
+        // the largest votes for which 10 seats can be apportioned (see `check_input`)
+        let k = Count::MAX / 440;
+        for (name, allocation) in ALLOCATIONS {
+            let result = run(allocation, 10, &[5 * k, 3 * k, 2 * k]);
+            assert_eq!(result, Ok(vec![5, 3, 2]), "{name}");
         }
-    };
+    }
+
+    #[test]
+    fn large_votes_that_differ_by_one() {
+        let a = Count::MAX / 32;
+        for (name, allocation) in ALLOCATIONS {
+            assert_eq!(run(allocation, 3, &[a, a - 1]), Ok(vec![2, 1]), "{name}");
+            assert_eq!(run(allocation, 3, &[a - 1, a]), Ok(vec![1, 2]), "{name}");
+        }
+    }
+
+    #[test]
+    fn too_large_votes_are_an_overflow() {
+        for (name, allocation) in ALLOCATIONS {
+            let result = run(allocation, 7, &[Count::MAX, 1, 1]);
+            assert_eq!(result, Err(AllocationError::Overflow), "{name}");
+            let result = run(allocation, 7, &[Count::MAX / 2 + 1, Count::MAX / 2 + 1, 1]);
+            assert_eq!(result, Err(AllocationError::Overflow), "{name}");
+        }
+    }
+
+    // Constructed examples for the rules before 2017, computed by hand: no official results from
+    // before 2017 have been validated with these methods (see the README).
+
+    #[test]
+    fn unrestricted_averages_before_2017() {
+        // The quota is 125, and only list 1 has 75% of it. It receives 5 whole seats and a surplus
+        // seat; until 2017, the averages 730/7 and 730/8 give it the remaining two seats as well,
+        // but since 2017 it can receive only one of them, after which list 2 has the highest
+        // average (85 against 730/8).
+        let votes = [730, 85, 75, 60, 50];
+        let before = run(allocate_per_surplus_before_2017, 8, &votes);
+        assert_eq!(before, Ok(vec![8, 0, 0, 0, 0]));
+        assert_eq!(
+            run(allocate_per_surplus, 8, &votes),
+            Ok(vec![7, 1, 0, 0, 0])
+        );
+    }
 
-    // this is my best interpretation from a 1917 law
-    round(0, true);
-    round(1, true);
-    round(0, false);
+    #[test]
+    fn combination_before_2017() {
+        // The quota is 111 1/9. Separately, lists 2 and 3 only receive a whole seat each, and the
+        // surplus seats go to lists 4 and 5 (78 8/9 and 58 8/9). Combined, lists 2 and 3 have 2
+        // whole seats and a surplus of 77 7/9, which beats list 5; the 3 seats of the combination
+        // are then divided with a quota of 100, so that list 2 receives the third seat.
+        let votes = [340, 155, 145, 190, 170];
+        let separate = run(allocate_per_surplus_before_2017, 9, &votes);
+        assert_eq!(separate, Ok(vec![3, 1, 1, 2, 2]));
+        let combined = |total_seats, votes: &[Votes], seats: &mut [Seats], ctx: &mut Context| {
+            allocate_combined_before_2017(total_seats, votes, seats, &[vec![1, 2]], ctx)
+        };
+        assert_eq!(run(combined, 9, &votes), Ok(vec![3, 2, 1, 2, 1]));
+
+        // In a body of 19 seats or more, the combination takes part in the largest averages, and
+        // takes the last seat of list 4 (3000/6 against 1900/4). Its 6 seats are divided with a
+        // quota of 500: 3 whole seats for list 2, 2 for list 3, and the surplus seat for list 3.
+        let votes = [3400, 1650, 1350, 1900, 1700];
+        let separate = run(allocate_per_average, 19, &votes);
+        assert_eq!(separate, Ok(vec![7, 3, 2, 4, 3]));
+        assert_eq!(run(combined, 19, &votes), Ok(vec![7, 3, 3, 3, 3]));
+    }
+
+    #[test]
+    fn shadow_apportionment_draws_no_lots() {
+        // without list exhaustion, lists 2 and 3 are tied for the last seat, but that does not
+        // affect the absolute majority of list 1
+        let votes = [Votes(55), Votes(25), Votes(25)];
+        let mut ballot = RecordedBallot::new(LowestListNumber);
+        let mut ctx = Context::new(&mut ballot).with_rules(Interpretation::Kiesraad);
+        let mut seats = [Seats::unlimited(), Seats::unlimited(), Seats::limited(2)];
+        let result = allocate(Seats::filled(11), &votes, &mut seats, &mut ctx);
+        assert_eq!(result, Ok(()));
+        assert_eq!(seats.map(|x| x.count()), [6, 3, 2]);
+        assert_eq!(ballot.draws, []);
+    }
+
+    #[test]
+    fn unrestricted_averages_keep_the_whole_seats() {
+        // list 2 is exhausted after its whole seats, and list 1 can receive only one seat by
+        // averages, so the last seat is left to the round of unrestricted averages, which does
+        // not apportion whole seats again
+        let votes = [Votes(43), Votes(267)];
+        let mut ballot = LowestListNumber;
+        let mut events = Vec::new();
+        let mut ctx =
+            Context::observed(&mut ballot, &mut events).with_rules(Interpretation::Kiesraad);
+        let mut seats = [Seats::limited(4), Seats::limited(3)];
+        let result = allocate(Seats::filled(5), &votes, &mut seats, &mut ctx);
+        assert_eq!(result, Ok(()));
+        assert_eq!(seats.map(|x| x.count()), [2, 3]);
+
+        let quotas = events.iter().filter(|x| matches!(x, Event::Quota(_)));
+        let whole_seats = events
+            .iter()
+            .filter(|x| matches!(x, Event::WholeSeats { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(quotas.collect::<Vec<_>>(), [&Event::Quota(frac(310, 5))]);
+        assert_eq!(whole_seats, [&Event::WholeSeats { party: 1, seats: 3 }]);
+        assert!(events.contains(&Event::Round(Round::UnrestrictedAverages)));
+    }
+
+    #[test]
+    fn party_lists_with_large_votes() {
+        let k = Count::MAX / 1000;
+        let mut ballot = LowestListNumber;
+        let mut ctx = Context::new(&mut ballot);
+        let votes = [
+            vec![Votes(3 * k), Votes(2 * k)],
+            vec![Votes(3 * k), Votes(2 * k)],
+        ];
+        let mut seats = [Seats::unlimited(); 2];
+        let result = allocate_party_lists(
+            Seats::filled(10),
+            &votes,
+            &mut seats,
+            &[vec![0, 1]],
+            &mut ctx,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(seats.map(|x| x.count()), [6, 4]);
+
+        let votes = [vec![Votes(Count::MAX), Votes(1)]];
+        let result = allocate_party_lists(
+            Seats::filled(10),
+            &votes,
+            &mut seats,
+            &[vec![0, 1]],
+            &mut ctx,
+        );
+        assert_eq!(result, Err(AllocationError::Overflow));
+    }
 }
@@ -0,0 +1,238 @@
+use crate::data::*;
+use crate::errata::find_list;
+use crate::kiesraad::{CsvError, CsvRegion};
+
+/// A lot that was officially drawn in a region, as recorded in a lots file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LotDraw {
+    /// The line in the lots file
+    pub line: u64,
+    /// The name or code of the region
+    pub region: String,
+    /// The names of the tied lists, or `#` followed by their position in the region (see
+    /// `Erratum::party`)
+    pub tied: Vec<String>,
+    /// The list that won the lot
+    pub winner: String,
+    /// A citation of the source of the outcome of the lot, e.g. the official report of the
+    /// session in which it was drawn
+    pub source: String,
+}
+
+/// A lot that was officially drawn, with the lists identified by their index in the region
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfficialDraw {
+    pub tied: Vec<usize>,
+    pub winner: usize,
+    pub source: String,
+}
+
+/// Read a lots file: lines of the form `Regio;Partijen;Winnaar;Bron`, after a header with these
+/// names, where `Partijen` are the tied lists separated by `|`. The lots of a region are listed in
+/// the order in which they were drawn. Lines starting with `#` are comments.
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let draws = read_lot_draws(
+///     "Regio;Partijen;Winnaar;Bron\n\
+///      Saba;WIPM|S.L.P.;S.L.P.;Proces-verbaal Saba, p. 3\n",
+/// )
+/// .unwrap();
+/// assert_eq!(draws[0].tied, ["WIPM", "S.L.P."]);
+///
+/// let single = read_lot_draws("Regio;Partijen;Winnaar;Bron\nSaba;WIPM;WIPM;p. 3\n");
+/// assert_eq!(single.unwrap_err().to_string(), "line 2: a lot is drawn between at least two lists");
+/// ```
+pub fn read_lot_draws(text: &str) -> Result<Vec<LotDraw>, CsvError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b';')
+        .comment(Some(b'#'))
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let header = reader.headers()?.clone();
+    if !header
+        .iter()
+        .map(str::trim)
+        .eq(["Regio", "Partijen", "Winnaar", "Bron"])
+    {
+        return Err(CsvError::Header(
+            header.iter().collect::<Vec<_>>().join(";"),
+        ));
+    }
+
+    let mut draws = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |x| x.line());
+        let problem = |problem: &str| CsvError::Row {
+            line,
+            problem: problem.to_string(),
+        };
+        if record.len() != 4 {
+            return Err(CsvError::Row {
+                line,
+                problem: format!("expected 4 columns, found {}", record.len()),
+            });
+        }
+
+        let tied = record[1]
+            .split('|')
+            .map(|x| x.trim().to_string())
+            .collect::<Vec<_>>();
+        if tied.len() < 2 {
+            return Err(problem("a lot is drawn between at least two lists"));
+        }
+        let winner = record[2].trim();
+        if !tied.iter().any(|x| x == winner) {
+            return Err(problem("the winner is not one of the tied lists"));
+        }
+        let source = record[3].trim();
+        if source.is_empty() {
+            return Err(problem("missing source"));
+        }
+
+        draws.push(LotDraw {
+            line,
+            region: record[0].trim().to_string(),
+            tied,
+            winner: winner.to_string(),
+            source: source.to_string(),
+        });
+    }
+
+    Ok(draws)
+}
+
+/// The official lots of every region, in the order of the `regions`. Every lot has to identify a
+/// region and its lists.
+pub fn official_draws(
+    regions: &[CsvRegion],
+    draws: &[LotDraw],
+) -> Result<Vec<Vec<OfficialDraw>>, CsvError> {
+    let mut official = vec![Vec::new(); regions.len()];
+    for draw in draws {
+        let problem = |problem: String| CsvError::Row {
+            line: draw.line,
+            problem,
+        };
+        let index = regions
+            .iter()
+            .position(|x| x.name == draw.region || x.code == draw.region)
+            .ok_or_else(|| problem(format!("no region {}", draw.region)))?;
+
+        let region = &regions[index];
+        let tied = draw
+            .tied
+            .iter()
+            .map(|party| find_list(region, party))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(problem)?;
+        official[index].push(OfficialDraw {
+            tied,
+            winner: find_list(region, &draw.winner).map_err(problem)?,
+            source: draw.source.clone(),
+        });
+    }
+
+    Ok(official)
+}
+
+/// Replay the official lots of a region. Every lot consumes the next official draw; if it was not
+/// drawn between the same lists, no lot can be drawn.
+pub struct OfficialBallot<'a>(std::slice::Iter<'a, OfficialDraw>);
+
+impl<'a> OfficialBallot<'a> {
+    pub fn new(draws: &'a [OfficialDraw]) -> Self {
+        OfficialBallot(draws.iter())
+    }
+
+    /// The number of official draws that have not been replayed
+    pub fn remaining(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Ballot for OfficialBallot<'_> {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        let draw = self.0.next()?;
+        let mut recorded = draw.tied.clone();
+        let mut tied = tied.to_vec();
+        recorded.sort();
+        tied.sort();
+        (recorded == tied).then_some(draw.winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kiesraad::read_kiesraad_csv;
+
+    fn regions() -> Vec<CsvRegion> {
+        read_kiesraad_csv(
+            "Regio;Code;Type;Partij;AantalStemmen;AantalZetels\n\
+             Saba;O9003;Partij;WIPM;600;3\n\
+             Saba;O9003;Partij;S.L.P.;300;1\n\
+             Saba;O9003;Partij;PLS;300;1\n",
+        )
+        .unwrap()
+    }
+
+    fn official(lots: &str) -> Result<Vec<Vec<OfficialDraw>>, String> {
+        let draws = read_lot_draws(&format!("Regio;Partijen;Winnaar;Bron\n{lots}")).unwrap();
+        official_draws(&regions(), &draws).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn replay() {
+        let official = official("O9003;S.L.P.|#3;#3;p. 3\n").unwrap();
+        assert_eq!(
+            official,
+            [vec![OfficialDraw {
+                tied: vec![1, 2],
+                winner: 2,
+                source: "p. 3".to_string(),
+            }]]
+        );
+
+        // the order of the tied lists does not matter, but every lot is drawn only once
+        let mut ballot = OfficialBallot::new(&official[0]);
+        assert_eq!(ballot.draw(&[2, 1]), Some(2));
+        assert_eq!(ballot.remaining(), 0);
+        assert_eq!(ballot.draw(&[1, 2]), None);
+
+        let mut ballot = OfficialBallot::new(&official[0]);
+        assert_eq!(ballot.draw(&[0, 1]), None);
+    }
+
+    #[test]
+    fn unknown_region_or_party() {
+        let region = official("Bonaire;S.L.P.|PLS;PLS;p. 3\n");
+        assert_eq!(region.unwrap_err(), "line 2: no region Bonaire");
+        let party = official("Saba;S.L.P.|MPB;S.L.P.;p. 3\n");
+        assert_eq!(party.unwrap_err(), "line 2: no list MPB");
+    }
+
+    #[test]
+    fn malformed_lots() {
+        let error = |lots: &str| {
+            read_lot_draws(&format!("Regio;Partijen;Winnaar;Bron\n{lots}"))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("Saba;S.L.P.|PLS;WIPM;p. 3\n"),
+            "line 2: the winner is not one of the tied lists"
+        );
+        assert_eq!(error("Saba;S.L.P.|PLS;PLS;\n"), "line 2: missing source");
+        assert_eq!(
+            error("Saba;S.L.P.|PLS;PLS\n"),
+            "line 2: expected 4 columns, found 3"
+        );
+        let header = read_lot_draws("Regio;Winnaar;Bron\n").unwrap_err();
+        assert_eq!(header.to_string(), "unexpected header: Regio;Winnaar;Bron");
+    }
+}
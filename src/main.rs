@@ -1,7 +1,8 @@
 #[cfg(feature = "validate")]
+use std::path::Path;
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use kiesraad_model::*;
 
 #[derive(Parser)]
@@ -9,6 +10,10 @@ use kiesraad_model::*;
 struct Cli {
     #[command(subcommand)]
     command: Command,
+    /// How to read the Kieswet where it leaves room for interpretation [default: literal, or as
+    /// given in the election file]
+    #[arg(long, global = true, value_enum)]
+    interpretation: Option<Reading>,
 }
 
 #[derive(Subcommand)]
@@ -16,16 +21,226 @@ enum Command {
     /// Run some example elections
     Demo,
     /// Run an election with the provided number of seats and votes
-    Allocate(AllocateArgs),
+    Allocate {
+        #[command(flatten)]
+        election: AllocateArgs,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+        format: DataFormat,
+    },
     /// Validate election results from CSV file(s)
     #[cfg(feature = "validate")]
-    Validate { files: Vec<PathBuf> },
+    Validate {
+        files: Vec<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+        format: DataFormat,
+        /// Also write the results as a JUnit XML report to this file
+        #[arg(long)]
+        junit: Option<PathBuf>,
+        /// List the corrections of the data that are in effect, as given in the errata files next
+        /// to the data files
+        #[arg(long)]
+        errata: bool,
+        /// Also write the results as a JSON report to this file
+        #[cfg(feature = "json")]
+        #[arg(long)]
+        json: Option<PathBuf>,
+    },
+    /// List the elections in CSV file(s) in which drawing lots decided a seat
+    #[cfg(feature = "validate")]
+    Lots { files: Vec<PathBuf> },
+    /// Find elections in which the literal text of the Kieswet and the reading of the Kiesraad
+    /// lead to different seats, among the elections in CSV file(s) and generated small councils
+    #[cfg(feature = "validate")]
+    Diverge {
+        files: Vec<PathBuf>,
+        /// Number of small council elections to generate, with an absolute majority and lists
+        /// that can be exhausted
+        #[arg(long, default_value_t = 10000)]
+        random: usize,
+        /// Seed for generating the elections
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Divide the seats of the Tweede Kamer over the lists that parties submitted in the kieskringen
+    #[cfg(feature = "validate")]
+    Lists(ListsArgs),
+    /// Compute the result of an election in an EML 510 file, and write it as an EML 520 document
+    #[cfg(feature = "eml")]
+    Export(ExportArgs),
+    /// Produce a report of a seat apportionment, in the style of the official report
+    Report(ReportArgs),
+    /// Determine who succeeds the members of a list when seats become vacant
+    Succession {
+        /// Number of seats obtained by the list
+        seats: usize,
+        /// Names of the candidates, in the order in which they are considered for a seat
+        #[arg(short, long, value_delimiter = ',')]
+        ranking: Vec<String>,
+        /// Changes in membership, in the order they occur: vacancy:NAME, declined:NAME,
+        /// leave:NAME or return:NAME
+        #[arg(value_parser = parse_change)]
+        changes: Vec<Change>,
+    },
+}
+
+fn parse_threshold(arg: &str) -> Result<Threshold, String> {
+    let number = |digits: &str| {
+        let (whole, decimals) = digits.split_once('.').unwrap_or((digits, ""));
+        let numerator = format!("{whole}{decimals}")
+            .parse()
+            .map_err(|_| format!("not a number: {digits}"))?;
+        let denominator = u32::try_from(decimals.len())
+            .ok()
+            .and_then(|n| Count::checked_pow(10, n))
+            .ok_or_else(|| format!("too many decimals: {digits}"))?;
+        Ok::<_, String>(frac(numerator, denominator))
+    };
+    let whole = |digits: &str| {
+        digits
+            .parse()
+            .map_err(|_| format!("not a whole number: {digits}"))
+    };
+    if let Some(percentage) = arg.strip_suffix('%') {
+        Ok(Threshold::Percentage(number(percentage)?))
+    } else if let Some(share) = arg.strip_suffix("quota") {
+        Ok(Threshold::Quota(number(share)?))
+    } else if let Some(seats) = arg.strip_suffix("seats").or(arg.strip_suffix("seat")) {
+        Ok(Threshold::Seats(whole(seats)?))
+    } else if let Some(votes) = arg.strip_suffix("votes") {
+        Ok(Threshold::Votes(whole(votes)?))
+    } else {
+        Err(format!(
+            "expected a percentage, a number of seats, a fraction of the quota or a number of \
+             votes (3%, 2seats, 0.75quota or 15000votes), got {arg}"
+        ))
+    }
 }
 
+fn parse_change(arg: &str) -> Result<Change, String> {
+    let (kind, name) = arg
+        .split_once(':')
+        .ok_or_else(|| format!("expected KIND:NAME, got {arg}"))?;
+    let name = name.to_string();
+    match kind {
+        "vacancy" => Ok(Change::Vacancy(name)),
+        "declined" => Ok(Change::Declined(name)),
+        "leave" => Ok(Change::Leave(name)),
+        "return" => Ok(Change::Return(name)),
+        _ => Err(format!("unknown kind of change: {kind}")),
+    }
+}
+
+#[cfg(feature = "validate")]
 #[derive(Args)]
-struct AllocateArgs {
+struct ListsArgs {
+    /// Total number of seats to allocate
+    seats: u64,
+    /// CSV file with the columns Kieskring, Partij, Lijst, AantalStemmen and AantalKandidaten
+    file: PathBuf,
+    /// Show all the steps taken in the apportionment
+    #[arg(long)]
+    trace: bool,
+    #[command(flatten)]
+    lots: LotArgs,
+}
+
+#[cfg(feature = "eml")]
+#[derive(Args)]
+struct ExportArgs {
     /// Total number of seats to allocate
     seats: u64,
+    /// EML 510 file with the total count ("Totaaltelling_*.eml.xml")
+    file: PathBuf,
+    /// Write the EML 520 document to this file, instead of standard output
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Take the elected candidates from this EML 520 file, e.g. the official result, instead of
+    /// determining them from the preference votes
+    #[arg(long)]
+    elected: Option<PathBuf>,
+    #[command(flatten)]
+    lots: LotArgs,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    /// Output format of the report
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(subcommand)]
+    source: ReportSource,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DataFormat {
+    Text,
+    /// Only machine-readable output, without the banner
+    #[cfg(feature = "json")]
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Reading {
+    /// The literal text of the law
+    Literal,
+    /// The reading of the Kiesraad software: lists without votes get no rest seats, the absolute
+    /// majority correction precedes list exhaustion, and seats that would remain unoccupied are
+    /// awarded by largest averages
+    Kiesraad,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Divisor {
+    DHondt,
+    SainteLague,
+    /// Sainte-Laguë with a first divisor of 1.4
+    ModifiedSainteLague,
+    Imperiali,
+    Adams,
+    Danish,
+    HuntingtonHill,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum QuotaName {
+    Hare,
+    Droop,
+    HagenbachBischoff,
+    Imperiali,
+}
+
+#[derive(Subcommand)]
+enum ReportSource {
+    /// Report on an election with the provided number of seats and votes
+    Allocate(AllocateArgs),
+    /// Report on a region in a CSV file
+    #[cfg(feature = "validate")]
+    Region {
+        file: PathBuf,
+        /// Name of the region, as it appears in the first column
+        region: String,
+        /// How lots are drawn; by default, the official lots of the region are replayed if they
+        /// are recorded
+        #[command(flatten)]
+        lots: LotArgs,
+    },
+}
+
+#[derive(Args)]
+struct AllocateArgs {
+    /// Total number of seats to allocate
+    #[cfg_attr(feature = "json", arg(required_unless_present = "election"))]
+    #[cfg_attr(not(feature = "json"), arg(required = true))]
+    seats: Option<u64>,
     /// Number of votes per party
     #[arg(short, long)]
     #[clap(num_args = 1.., value_delimiter=',')]
@@ -34,57 +249,566 @@ struct AllocateArgs {
     #[arg(short, long, default_value=None)]
     #[clap(num_args = 1.., value_delimiter=',')]
     candidates: Option<Vec<u64>>,
+    /// Name of every party
+    #[arg(long)]
+    #[clap(num_args = 1.., value_delimiter=',')]
+    names: Vec<String>,
+    /// Read the votes and candidates per party from an EML 510 file, instead
+    #[cfg(feature = "eml")]
+    #[arg(long, conflicts_with_all = ["votes", "candidates", "names"])]
+    eml: Option<PathBuf>,
+    /// Also show which candidates are elected, based on the preference votes in the EML file
+    #[cfg(feature = "eml")]
+    #[arg(long, requires = "eml")]
+    elected: bool,
+    /// Read the description of the election from a JSON file, instead
+    #[cfg(feature = "json")]
+    #[arg(long, conflicts_with_all = ["seats", "votes", "candidates", "names", "eml", "national", "divisor", "quota"])]
+    election: Option<PathBuf>,
     /// Use a voting threshold of one whole seat, as used in Dutch national elections
     #[arg(short, long)]
     national: bool,
+    /// Apportion all seats by highest averages using this divisor method, instead of the method
+    /// prescribed by Dutch law
+    #[arg(long, value_enum, conflicts_with = "national")]
+    divisor: Option<Divisor>,
+    /// Apportion the seats by largest surpluses using this quota, with the threshold and rounds of
+    /// Dutch bodies of less than 19 seats, instead of the method prescribed by Dutch law
+    #[arg(long, value_enum, conflicts_with_all = ["national", "divisor"])]
+    quota: Option<QuotaName>,
+    /// Only parties with at least this many votes are eligible for seats: a percentage of the votes
+    /// (3%), a number of seats (2seats), a fraction of the quota (0.75quota) or a number of votes
+    /// (15000votes)
+    #[arg(long, value_parser = parse_threshold)]
+    threshold: Option<Threshold>,
+    /// Show all the steps taken in the apportionment
+    #[arg(long)]
+    trace: bool,
+    #[command(flatten)]
+    lots: LotArgs,
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+struct LotArgs {
+    /// Seed for drawing lots between tied parties
+    #[arg(long)]
+    seed: Option<u64>,
+    /// File with the winners of the lots to be drawn, as list numbers, one per line
+    #[arg(long)]
+    lots: Option<PathBuf>,
+    /// Ask which party wins whenever lots need to be drawn
+    #[arg(long)]
+    interactive: bool,
+    /// Award tied seats to the party with the lowest list number
+    #[arg(long)]
+    lowest: bool,
+}
+
+impl AllocateArgs {
+    /// The totals of the first contest in the EML file, if one was given
+    #[cfg(feature = "eml")]
+    fn eml(&self) -> Option<Totals> {
+        let path = self.eml.as_ref()?;
+        let xml = std::fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("cannot read {}: {err}", path.display());
+            std::process::exit(1)
+        });
+        match read_eml_510(&xml) {
+            Ok(totals) if !totals.is_empty() => totals.into_iter().next(),
+            Ok(_) => {
+                eprintln!("{}: no contests found", path.display());
+                std::process::exit(1)
+            }
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                std::process::exit(1)
+            }
+        }
+    }
+
+    /// The totals in the EML file and the candidates that are elected in the `outcome`, if they are
+    /// to be shown
+    #[cfg(feature = "eml")]
+    fn elected(&self, outcome: &Outcome) -> Option<(Totals, Vec<Vec<Elected>>)> {
+        if !self.elected {
+            return None;
+        }
+        let totals = self.eml()?;
+        let seats = outcome.parties.iter().map(|x| x.seats).collect::<Vec<_>>();
+        let elected = totals.elected(&seats);
+        Some((totals, elected))
+    }
+
+    /// The election described by the arguments, following the `rules` if they are given
+    fn election(&self, rules: Option<Rules>) -> Election {
+        #[cfg(feature = "json")]
+        if let Some(path) = &self.election {
+            let json = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                eprintln!("cannot read {}: {err}", path.display());
+                std::process::exit(1)
+            });
+            let election = serde_json::from_str::<Election>(&json).unwrap_or_else(|err| {
+                eprintln!("{}: {err}", path.display());
+                std::process::exit(1)
+            });
+            return Election {
+                rules: rules.unwrap_or(election.rules),
+                threshold: self.threshold.or(election.threshold),
+                ..election
+            };
+        }
+
+        let seats = self.seats.unwrap();
+        let method = if self.national {
+            Method::National
+        } else if let Some(divisor) = self.divisor {
+            Method::Divisor(match divisor {
+                Divisor::DHondt => DivisorMethod::DHondt,
+                Divisor::SainteLague => DivisorMethod::SainteLague,
+                Divisor::ModifiedSainteLague => DivisorMethod::ModifiedSainteLague,
+                Divisor::Imperiali => DivisorMethod::Imperiali,
+                Divisor::Adams => DivisorMethod::Adams,
+                Divisor::Danish => DivisorMethod::Danish,
+                Divisor::HuntingtonHill => DivisorMethod::HuntingtonHill,
+            })
+        } else if let Some(quota) = self.quota {
+            Method::Quota(QuotaMethod {
+                quota: match quota {
+                    QuotaName::Hare => Quota::Hare,
+                    QuotaName::Droop => Quota::Droop,
+                    QuotaName::HagenbachBischoff => Quota::HagenbachBischoff,
+                    QuotaName::Imperiali => Quota::Imperiali,
+                },
+                ..QuotaMethod::DUTCH
+            })
+        } else {
+            Method::for_seats(seats)
+        };
+
+        #[cfg(feature = "eml")]
+        if let Some(totals) = self.eml() {
+            return Election {
+                body: Some(totals.election.name),
+                seats,
+                method,
+                parties: totals
+                    .lists
+                    .into_iter()
+                    .map(|list| Party {
+                        name: list.name,
+                        list: list.id.parse().ok(),
+                        votes: list.votes,
+                        candidates: Some(list.candidates.len() as Count),
+                    })
+                    .collect(),
+                combinations: Vec::new(),
+                rules: rules.unwrap_or_default(),
+                threshold: self.threshold,
+            };
+        }
+
+        Election {
+            body: None,
+            seats,
+            method,
+            parties: self
+                .votes
+                .iter()
+                .enumerate()
+                .map(|(i, &votes)| Party {
+                    name: self.names.get(i).cloned().unwrap_or_default(),
+                    list: None,
+                    votes: Votes(votes),
+                    candidates: self.candidates.as_ref().and_then(|x| x.get(i).copied()),
+                })
+                .collect(),
+            combinations: Vec::new(),
+            rules: rules.unwrap_or_default(),
+            threshold: self.threshold,
+        }
+    }
+}
+
+impl LotArgs {
+    /// Whether any way of drawing lots was chosen
+    #[cfg(feature = "validate")]
+    fn is_given(&self) -> bool {
+        self.seed.is_some() || self.lots.is_some() || self.interactive || self.lowest
+    }
+
+    fn ballot(&self) -> Box<dyn Ballot> {
+        if let Some(seed) = self.seed {
+            Box::new(RandomBallot::seeded(seed))
+        } else if let Some(path) = &self.lots {
+            let schedule = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                eprintln!("cannot read {}: {err}", path.display());
+                std::process::exit(1)
+            });
+            let winners = schedule
+                .lines()
+                .map(|line| line.split('#').next().unwrap().trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| match line.parse::<usize>() {
+                    Ok(n) if n > 0 => n - 1,
+                    _ => {
+                        eprintln!("invalid list number in {}: {line}", path.display());
+                        std::process::exit(1)
+                    }
+                })
+                .collect::<Vec<_>>();
+            Box::new(ScheduledBallot::new(winners))
+        } else if self.interactive {
+            Box::new(Prompt)
+        } else if self.lowest {
+            Box::new(LowestListNumber)
+        } else {
+            Box::new(RandomBallot::from_entropy())
+        }
+    }
+}
+
+/// Ask the user on the terminal who won a lot
+struct Prompt;
+
+impl Ballot for Prompt {
+    fn draw(&mut self, tied: &[usize]) -> Option<usize> {
+        let numbers = tied.iter().map(|i| (i + 1).to_string()).collect::<Vec<_>>();
+        loop {
+            eprint!(
+                "lots are drawn between lists {}; winner? ",
+                numbers.join(", ")
+            );
+            let mut answer = String::new();
+            if std::io::stdin().read_line(&mut answer).ok()? == 0 {
+                return None;
+            }
+            if let Some(i) = numbers.iter().position(|n| n == answer.trim()) {
+                return Some(tied[i]);
+            }
+        }
+    }
 }
 
 fn main() {
-    println!(
-        "Copyright (C) 2025  Marc Schoolderman
+    let cli = Cli::parse();
+
+    let banner = "Copyright (C) 2025  Marc Schoolderman
 This program comes with ABSOLUTELY NO WARRANTY
 This is free software, and you are welcome to redistribute it
 under certain conditions, see the file LICENSE
-"
-    );
+";
+    // reports, exports and machine-readable output are documents of their own
+    let document = match &cli.command {
+        Command::Report(_) => true,
+        #[cfg(feature = "eml")]
+        Command::Export(_) => true,
+        Command::Allocate { format, .. } => *format != DataFormat::Text,
+        #[cfg(feature = "validate")]
+        Command::Validate { format, .. } => *format != DataFormat::Text,
+        _ => false,
+    };
+    if document {
+        eprintln!("{banner}");
+    } else {
+        println!("{banner}");
+    }
 
-    let cli = Cli::parse();
+    let reading = cli.interpretation.map(|reading| {
+        match reading {
+            Reading::Literal => Interpretation::Literal,
+            Reading::Kiesraad => Interpretation::Kiesraad,
+        }
+        .rules()
+    });
+    #[cfg(any(feature = "validate", feature = "eml"))]
+    let rules = reading.unwrap_or_default();
 
     match &cli.command {
         Command::Demo => demo(),
-        Command::Allocate(args) => {
-            let votes = args.votes.iter().map(|v| Votes(*v)).collect::<Vec<Votes>>();
-            println!(
-                "running an election for {} seats, parties: {votes:?}, using largest {}",
-                args.seats,
-                if args.national {
-                    "averages (with voting threshold of one whole seat)"
-                } else if args.seats >= 19 {
-                    "averages"
-                } else {
-                    "surpluses"
+        Command::Allocate { election, format } => {
+            let args = election;
+            let election = args.election(reading);
+            if *format == DataFormat::Text {
+                println!(
+                    "running an election for {} seats, parties: {:?}, using {}",
+                    election.seats,
+                    election.votes(),
+                    election.method
+                );
+            }
+            let outcome = apportioned(election.run(&mut args.lots.ballot()));
+            match format {
+                DataFormat::Text => {
+                    for (event, step) in std::iter::zip(&outcome.trace, outcome.steps()) {
+                        if args.trace || matches!(event, Event::Lot { .. }) {
+                            println!("{step}");
+                        }
+                    }
+                    print_outcome(&outcome);
+                    #[cfg(feature = "eml")]
+                    if let Some((totals, elected)) = args.elected(&outcome) {
+                        print_elected(&outcome, &totals.candidates(), &elected);
+                    }
+                }
+                #[cfg(feature = "json")]
+                DataFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&outcome).unwrap());
                 }
+            }
+        }
+        #[cfg(feature = "validate")]
+        Command::Validate {
+            files,
+            format,
+            junit,
+            errata,
+            #[cfg(feature = "json")]
+            json,
+        } => {
+            if *format == DataFormat::Text {
+                println!("Validating {} files...", files.len());
+            }
+            let results = validate(files, rules);
+            match format {
+                DataFormat::Text => {
+                    if *errata {
+                        print_corrections(&results);
+                    }
+                    print_validation(&results)
+                }
+                #[cfg(feature = "json")]
+                DataFormat::Json => println!("{}", validation_json(&results)),
+            }
+            if let Some(path) = junit {
+                write_report(path, &validation_junit(&results));
+            }
+            #[cfg(feature = "json")]
+            if let Some(path) = json {
+                write_report(path, &validation_json(&results));
+            }
+            match results.exit_code() {
+                0 => {}
+                code => std::process::exit(code),
+            }
+        }
+        #[cfg(feature = "validate")]
+        Command::Lots { files } => lots(files, rules),
+        #[cfg(feature = "validate")]
+        Command::Diverge {
+            files,
+            random,
+            seed,
+        } => diverge(files, *random, *seed),
+        #[cfg(feature = "validate")]
+        Command::Lists(args) => party_lists(args, rules),
+        #[cfg(feature = "eml")]
+        Command::Export(args) => export(args, rules),
+        Command::Report(args) => report(args, reading),
+        Command::Succession {
+            seats,
+            ranking,
+            changes,
+        } => succession(*seats, ranking, changes),
+    }
+}
+
+fn succession(seats: usize, ranking: &[String], changes: &[Change]) {
+    let mut list = ListSeats::new(ranking.to_vec(), seats);
+    println!("members: {}", list.members().join(", "));
+    for change in changes {
+        println!("{change}");
+        match list.apply(change) {
+            Ok(result) => {
+                for x in result {
+                    match x {
+                        Succession::Appointed(name) => println!("  {name} is appointed"),
+                        Succession::Replacement(name) => {
+                            println!("  {name} is appointed as temporary replacement")
+                        }
+                        Succession::ReplacementEnded(name) => {
+                            println!("  {name} is no longer a temporary replacement")
+                        }
+                        Succession::Vacant => {
+                            println!("  the list is exhausted, the seat stays vacant")
+                        }
+                    }
+                }
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1)
+            }
+        }
+    }
+    println!("members: {}", list.members().join(", "));
+}
+
+#[cfg(feature = "eml")]
+fn export(args: &ExportArgs, rules: Rules) {
+    let xml = std::fs::read_to_string(&args.file).unwrap_or_else(|err| {
+        eprintln!("cannot read {}: {err}", args.file.display());
+        std::process::exit(1)
+    });
+    let totals = match read_eml_510(&xml) {
+        Ok(totals) if totals.len() == 1 => totals.into_iter().next().unwrap(),
+        Ok(totals) => {
+            eprintln!(
+                "{}: expected a single contest, found {}",
+                args.file.display(),
+                totals.len()
             );
-            let mut seats = if let Some(cands) = &args.candidates {
-                cands.iter().cloned().map(Seats::limited).collect()
-            } else {
-                vec![Seats::unlimited(); votes.len()]
+            std::process::exit(1)
+        }
+        Err(err) => {
+            eprintln!("{}: {err}", args.file.display());
+            std::process::exit(1)
+        }
+    };
+
+    let code = totals.election.code().unwrap_or_else(|err| {
+        eprintln!("{}: {err}", args.file.display());
+        std::process::exit(1)
+    });
+    if let Some(seats) = code.seats()
+        && seats != args.seats
+    {
+        eprintln!(
+            "the {} of {code} has {seats} seats, not {}",
+            code.body, args.seats
+        );
+        std::process::exit(1)
+    }
+
+    let votes = totals.votes();
+    let mut seats = totals.candidate_limits();
+    let mut ballot = args.lots.ballot();
+    let ctx = &mut Context::new(&mut ballot).with_rules(rules);
+    apportioned(code.method(args.seats, false).allocate(
+        Seats::filled(args.seats),
+        &votes,
+        &mut seats,
+        &[],
+        ctx,
+    ));
+
+    let elected = args.elected.as_ref().map(|path| {
+        let result = std::fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {err}", path.display()))
+            .and_then(|xml| {
+                let contest = read_eml_520(&xml)
+                    .map_err(|err| format!("{}: {err}", path.display()))?
+                    .into_iter()
+                    .find(|x| x.contest == totals.contest)
+                    .ok_or_else(|| format!("{}: no contest {}", path.display(), totals.contest))?;
+                contest
+                    .elected_in(&totals)
+                    .map_err(|err| format!("{}: {err}", path.display()))
+            });
+        result.unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1)
+        })
+    });
+
+    let document = write_eml_520(&totals, &seats, elected.as_deref());
+    match &args.output {
+        Some(path) => std::fs::write(path, document).unwrap_or_else(|err| {
+            eprintln!("cannot write {}: {err}", path.display());
+            std::process::exit(1)
+        }),
+        None => print!("{document}"),
+    }
+}
+
+fn report(args: &ReportArgs, rules: Option<Rules>) {
+    let report = match &args.source {
+        ReportSource::Allocate(args) => {
+            let election = args.election(rules);
+            let outcome = apportioned(election.run(&mut args.lots.ballot()));
+            let title = match &election.body {
+                Some(body) => format!("Election for {} seats in {body}", election.seats),
+                None => format!("Election for {} seats", election.seats),
             };
-            if args.national {
-                allocate_national(Seats::filled(args.seats), &votes, &mut seats);
-            } else {
-                allocate(Seats::filled(args.seats), &votes, &mut seats);
-            }
-            print_seats(seats.into_iter());
+            let report = Report::for_outcome(&title, &outcome);
+            #[cfg(feature = "eml")]
+            let report = match args.elected(&outcome) {
+                Some((totals, elected)) => report.with_elected(&totals.candidates(), &elected),
+                None => report,
+            };
+            report
         }
         #[cfg(feature = "validate")]
-        Command::Validate { files } => {
-            println!("Validating {} files...", files.len());
-            validate(files);
+        ReportSource::Region { file, region, lots } => {
+            let Some(data) = read_regions(file).into_iter().find(|x| x.id == *region) else {
+                eprintln!("no region named {region} in {}", file.display());
+                std::process::exit(1)
+            };
+            let election = Election {
+                rules: rules.unwrap_or_default(),
+                ..data.election()
+            };
+            let outcome = if data.draws.is_empty() || lots.is_given() {
+                election.run(&mut lots.ballot())
+            } else {
+                election.run(&mut OfficialBallot::new(&data.draws))
+            };
+            let outcome = apportioned(outcome);
+            let title = format!("Election for {} seats in {region}", election.seats);
+            Report::for_outcome(&title, &outcome)
+        }
+    };
+
+    let format = match args.format {
+        OutputFormat::Text => ReportFormat::Text,
+        OutputFormat::Markdown => ReportFormat::Markdown,
+        OutputFormat::Html => ReportFormat::Html,
+    };
+    print!("{}", report.render(format));
+}
+
+/// Stop the program if a seat apportionment could not be performed
+fn apportioned<T>(result: Result<T, AllocationError>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("cannot apportion the seats: {err}");
+        std::process::exit(1)
+    })
+}
+
+/// Print the candidates that fill the seats of every party, marking those that were elected by
+/// preference votes
+#[cfg(feature = "eml")]
+fn print_elected(outcome: &Outcome, candidates: &[Vec<Candidate>], elected: &[Vec<Elected>]) {
+    println!("elected candidates (* by preference votes):");
+    for (label, elected) in std::iter::zip(outcome.labels(), elected) {
+        let names = elected
+            .iter()
+            .map(|x| {
+                let name = &candidates[x.list][x.position].name;
+                if x.preference {
+                    format!("{name}*")
+                } else {
+                    name.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        match names.is_empty() {
+            true => println!("{label}: -"),
+            false => println!("{label}: {}", names.join(", ")),
         }
     }
 }
 
+/// Print the seats of every party, by name if the parties have names
+fn print_outcome(outcome: &Outcome) {
+    if outcome.parties.iter().all(|x| x.name.is_empty()) {
+        return print_seats(outcome.seats().into_iter());
+    }
+    for (label, party) in std::iter::zip(outcome.labels(), &outcome.parties) {
+        println!("list {label}: {}", party.seats);
+    }
+}
+
 fn print_seats(seats: impl Iterator<Item = Seats>) {
     print!("result = ");
     for seat in seats {
@@ -110,7 +834,12 @@ fn demo() {
             }
         );
         let mut seats = vec![Seats::unlimited(); votes.len()];
-        allocate(Seats::filled(target), &votes, &mut seats);
+        apportioned(allocate(
+            Seats::filled(target),
+            &votes,
+            &mut seats,
+            &mut Context::new(&mut RandomBallot::from_entropy()),
+        ));
         print_seats(seats.into_iter());
         println!("======");
     }
@@ -127,7 +856,12 @@ fn demo() {
     fn run_national_election(votes: Vec<Votes>) {
         println!("running an election for Tweede Kamer");
         let mut seats = vec![Seats::unlimited(); votes.len()];
-        allocate_national(Seats::filled(150), &votes, &mut seats);
+        apportioned(allocate_national(
+            Seats::filled(150),
+            &votes,
+            &mut seats,
+            &mut Context::new(&mut RandomBallot::from_entropy()),
+        ));
         print_seats(seats.into_iter());
         println!("======");
     }
@@ -165,119 +899,1518 @@ fn demo() {
     println!("a corner case in our national voting system");
     let votes = votes![33, 7];
     let mut seats = vec![Seats::limited(2), Seats::limited(13)];
-    allocate(Seats::filled(4), &votes, &mut seats);
+    apportioned(allocate(
+        Seats::filled(4),
+        &votes,
+        &mut seats,
+        &mut Context::new(&mut LowestListNumber),
+    ));
     print_seats(seats.into_iter());
 
     println!("a weird consequence of a little sentence in the law");
     let votes = votes![33, 7, 0];
     let mut seats = vec![Seats::limited(2), Seats::limited(12), Seats::limited(2)];
-    allocate(Seats::filled(4), &votes, &mut seats);
+    apportioned(allocate(
+        Seats::filled(4),
+        &votes,
+        &mut seats,
+        &mut Context::new(&mut LowestListNumber),
+    ));
     print_seats(seats.into_iter());
 }
 
+/// The results of a single region in a Kiesraad CSV file
 #[cfg(feature = "validate")]
-fn validate(data_sources: &Vec<PathBuf>) {
-    for data_source in data_sources {
-        let records = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .delimiter(b';')
-            .from_path(data_source)
-            .unwrap()
-            .records()
-            .collect::<Result<Vec<_>, _>>()
-            .unwrap();
-
-        let ignored = |s: &str| {
-            [
-                "AantalBlancoStemmen",
-                "AantalGeldigeStemmen",
-                "AantalOngeldigeStemmen",
-                "Kiesgerechtigden",
-                "Opkomst",
-            ]
-            .contains(&s)
+#[derive(Clone, Debug)]
+struct Region {
+    /// The identifier of the election this region is part of, e.g. TK19180702_Nederland (see
+    /// `ElectionCode`)
+    election: String,
+    id: String,
+    names: Vec<String>,
+    votes: Vec<Votes>,
+    outcome: Vec<Seats>,
+    candidates: Vec<Seats>,
+    combinations: Vec<Vec<usize>>,
+    /// The errata that were applied to the official data
+    corrections: Vec<Correction>,
+    /// The lots that were officially drawn, in order
+    draws: Vec<OfficialDraw>,
+}
+
+#[cfg(feature = "validate")]
+impl Region {
+    fn total_seats(&self) -> Count {
+        self.outcome.iter().map(|x| x.count()).sum()
+    }
+
+    /// The election that took place in this region
+    fn election(&self) -> Election {
+        Election {
+            body: Some(self.id.clone()),
+            seats: self.total_seats(),
+            method: self.method(),
+            parties: (0..self.votes.len())
+                .map(|i| Party {
+                    name: self.names[i].clone(),
+                    list: None,
+                    votes: self.votes[i],
+                    candidates: Some(self.candidates[i].limit).filter(|&x| x != Count::MAX),
+                })
+                .collect(),
+            combinations: self.combinations.clone(),
+            rules: Rules::default(),
+            threshold: None,
+        }
+    }
+
+    /// The identifier of the election, if it is one that the Kiesraad uses
+    fn code(&self) -> Option<ElectionCode> {
+        self.election.parse().ok()
+    }
+
+    /// The method of apportionment, based on the kind of election; an election that cannot be
+    /// identified is taken to be one for a council
+    fn method(&self) -> Method {
+        match self.code() {
+            Some(code) => code.method(self.total_seats(), !self.combinations.is_empty()),
+            None => Method::for_seats(self.total_seats()),
+        }
+    }
+
+    /// Run the election for this region
+    fn allocate(&self, ctx: &mut Context) -> Result<Vec<Seats>, AllocationError> {
+        let mut seats = self.candidates.clone();
+        self.method().allocate(
+            Seats::filled(self.total_seats()),
+            &self.votes,
+            &mut seats,
+            &self.combinations,
+            ctx,
+        )?;
+        Ok(seats)
+    }
+
+    /// Run the election for this region with the lots that were officially drawn, which have to
+    /// be exactly the lots that are needed
+    fn replay(&self, rules: Rules) -> Result<Vec<Count>, String> {
+        let mut ballot = OfficialBallot::new(&self.draws);
+        let seats = self
+            .allocate(&mut Context::new(&mut ballot).with_rules(rules))
+            .map_err(|err| format!("cannot replay the official lots: {err}"))?;
+        match ballot.remaining() {
+            0 => Ok(seats.iter().map(|x| x.count()).collect()),
+            n => Err(format!(
+                "the official lots include {n} that were not needed"
+            )),
+        }
+    }
+
+    /// A description of a lot that was officially drawn
+    fn describe_draw(&self, draw: &OfficialDraw) -> String {
+        let tied = draw
+            .tied
+            .iter()
+            .map(|&i| self.names[i].as_str())
+            .collect::<Vec<_>>();
+        format!(
+            "{} won the lot between {} ({})",
+            self.names[draw.winner],
+            tied.join(" and "),
+            draw.source
+        )
+    }
+
+    /// Determine all possible outcomes of the election, including their probability
+    fn possible_outcomes(
+        &self,
+        rules: Rules,
+    ) -> Result<Vec<(Vec<Count>, Fraction)>, AllocationError> {
+        possible_outcomes(|ctx| {
+            ctx.rules = rules;
+            self.allocate(ctx)
+        })
+    }
+
+    /// Determine all possible outcomes of the election, with the lists in a random order to check
+    /// that the outcome does not depend on it. The outcomes have the lists in the original order.
+    #[cfg(feature = "rand-validate")]
+    fn shuffled_outcomes(
+        &self,
+        rules: Rules,
+    ) -> Result<Vec<(Vec<Count>, Fraction)>, AllocationError> {
+        let mut region = self.clone();
+        let order = region.shuffle();
+        Ok(region
+            .possible_outcomes(rules)?
+            .into_iter()
+            .map(|(seats, probability)| {
+                let mut original = vec![0; seats.len()];
+                for (&i, count) in std::iter::zip(&order, seats) {
+                    original[i] = count;
+                }
+                (original, probability)
+            })
+            .collect())
+    }
+
+    /// Put the lists in a random order, returning the original index of every list
+    #[cfg(feature = "rand-validate")]
+    fn shuffle(&mut self) -> Vec<usize> {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+        let mut rng = StdRng::from_os_rng();
+        let mut order = (0..self.votes.len()).collect::<Vec<_>>();
+        order.shuffle(&mut rng);
+
+        self.names = order.iter().map(|&i| self.names[i].clone()).collect();
+        self.votes = order.iter().map(|&i| self.votes[i]).collect();
+        self.outcome = order.iter().map(|&i| self.outcome[i]).collect();
+        self.candidates = order.iter().map(|&i| self.candidates[i]).collect();
+        for members in &mut self.combinations {
+            for member in members.iter_mut() {
+                *member = order.iter().position(|&i| i == *member).unwrap();
+            }
+        }
+        order
+    }
+}
+
+/// Read the regions in a file, or exit if that is not possible
+#[cfg(feature = "validate")]
+fn read_regions(data_source: &Path) -> Vec<Region> {
+    try_read_regions(data_source).unwrap_or_else(|err| {
+        eprintln!("{}: {err}", data_source.display());
+        std::process::exit(1)
+    })
+}
+
+#[cfg(feature = "validate")]
+fn try_read_regions(data_source: &Path) -> Result<Vec<Region>, String> {
+    #[cfg(feature = "eml")]
+    if data_source.extension().is_some_and(|x| x == "xml") {
+        return read_eml_regions(data_source);
+    }
+
+    let file_name = data_source
+        .file_stem()
+        .ok_or("not a file")?
+        .to_string_lossy();
+    let election = file_name.strip_prefix("uitslag_").unwrap_or(&file_name);
+
+    let text = std::fs::read_to_string(data_source).map_err(|err| err.to_string())?;
+    let mut regions = read_kiesraad_csv(&text).map_err(|err| err.to_string())?;
+
+    // corrections of the data are kept in a file next to it, e.g. uitslag_TK19180702_Nederland.errata
+    let errata_source = data_source.with_extension("errata");
+    let corrections = if errata_source.exists() {
+        let in_errata = |err: CsvError| format!("{}: {err}", errata_source.display());
+        let text = std::fs::read_to_string(&errata_source).map_err(|err| err.to_string())?;
+        let errata = read_errata(&text).map_err(in_errata)?;
+        apply_errata(&mut regions, &errata).map_err(in_errata)?
+    } else {
+        Vec::new()
+    };
+
+    // the same goes for the outcomes of lots that were drawn, e.g. uitslag_GR20260318_Gemeente.loting
+    let lots_source = data_source.with_extension("loting");
+    let draws = if lots_source.exists() {
+        let in_lots = |err: CsvError| format!("{}: {err}", lots_source.display());
+        let text = std::fs::read_to_string(&lots_source).map_err(|err| err.to_string())?;
+        let draws = read_lot_draws(&text).map_err(in_lots)?;
+        official_draws(&regions, &draws).map_err(in_lots)?
+    } else {
+        vec![Vec::new(); regions.len()]
+    };
+
+    let regions = std::iter::zip(regions, draws)
+        .map(|(region, draws)| Region {
+            corrections: corrections
+                .iter()
+                .filter(|x| x.erratum.region == region.name || x.erratum.region == region.code)
+                .cloned()
+                .collect(),
+            draws,
+            election: election.to_string(),
+            names: region.lists.iter().map(|x| x.name.clone()).collect(),
+            votes: region.votes(),
+            outcome: region.outcome(),
+            candidates: region.candidate_limits(),
+            combinations: region
+                .combinations
+                .into_iter()
+                .map(|(_, members)| members)
+                .collect(),
+            id: region.name,
+        })
+        .collect();
+
+    Ok(regions)
+}
+
+/// Read the regions in an EML 510 file ("Totaaltelling_*.eml.xml"), taking the official outcome
+/// from the EML 520 file next to it ("Resultaat_*.eml.xml")
+#[cfg(all(feature = "validate", feature = "eml"))]
+fn read_eml_regions(data_source: &Path) -> Result<Vec<Region>, String> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map_err(|err| format!("cannot read {}: {err}", path.display()))
+    };
+
+    let file_name = data_source
+        .file_name()
+        .ok_or("not a file")?
+        .to_string_lossy();
+    let Some(name) = file_name.strip_prefix("Totaaltelling_") else {
+        return Err("expected a Totaaltelling_ file".to_string());
+    };
+    let result_source = data_source.with_file_name(format!("Resultaat_{name}"));
+
+    let totals = read_eml_510(&read(data_source)?).map_err(|err| err.to_string())?;
+    let results = read_eml_520(&read(&result_source)?)
+        .map_err(|err| format!("{}: {err}", result_source.display()))?;
+
+    let single = totals.len() == 1;
+    Ok(totals
+        .into_iter()
+        .map(|contest| {
+            let result = results.iter().find(|x| x.contest == contest.contest);
+            let outcome = contest
+                .lists
+                .iter()
+                .map(|list| {
+                    let seats = result
+                        .and_then(|x| x.seats.iter().find(|(id, _)| *id == list.id))
+                        .map(|(_, seats)| *seats)
+                        .unwrap_or_default();
+                    Seats::filled(seats)
+                })
+                .collect();
+
+            Region {
+                id: match &contest.election.domain {
+                    Some(domain) => domain.clone(),
+                    None if single => contest.election.name.clone(),
+                    None => contest.contest.clone(),
+                },
+                names: contest.lists.iter().map(|x| x.name.clone()).collect(),
+                votes: contest.votes(),
+                outcome,
+                candidates: contest.candidate_limits(),
+                combinations: Vec::new(),
+                corrections: Vec::new(),
+                draws: Vec::new(),
+                election: match contest.election.code() {
+                    Ok(code) => code.to_string(),
+                    Err(_) => contest.election.id,
+                },
+            }
+        })
+        .collect())
+}
+
+/// How the official outcome of a region compares to the computed outcomes
+#[cfg(feature = "validate")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+enum Verdict {
+    /// The official outcome is the only possible outcome
+    Pass,
+    /// The official outcome is one of several, and follows from the lots that were officially drawn
+    Drawn,
+    /// The official outcome is one of several, depending on the drawing of lots, but it is not
+    /// recorded how the lots were officially drawn
+    LotDependent,
+    /// The official outcome is not a possible outcome
+    Fail,
+    /// The seats could not be apportioned
+    Error,
+}
+
+#[cfg(feature = "validate")]
+impl Verdict {
+    /// Whether the official outcome was reproduced
+    fn is_valid(self) -> bool {
+        matches!(self, Verdict::Pass | Verdict::Drawn | Verdict::LotDependent)
+    }
+}
+
+/// The validation of a single region
+#[cfg(feature = "validate")]
+#[derive(Debug)]
+struct RegionCheck {
+    region: Region,
+    /// Every possible outcome, with its probability
+    outcomes: Vec<(Vec<Count>, Fraction)>,
+    error: Option<AllocationError>,
+    /// The outcome with the lots that were officially drawn, if any were recorded
+    replay: Option<Result<Vec<Count>, String>>,
+}
+
+#[cfg(feature = "validate")]
+impl RegionCheck {
+    fn new(region: Region, rules: Rules) -> RegionCheck {
+        // Due to drawing of lots, as happened in GR 2026 at Roermond, there can be multiple
+        // correct outcomes of an election.
+        #[cfg(feature = "rand-validate")]
+        let outcomes = region.shuffled_outcomes(rules);
+        #[cfg(not(feature = "rand-validate"))]
+        let outcomes = region.possible_outcomes(rules);
+        let (outcomes, error) = match outcomes {
+            Ok(outcomes) => (outcomes, None),
+            Err(err) => (Vec::new(), Some(err)),
         };
+        let replay = (!region.draws.is_empty()).then(|| region.replay(rules));
+        RegionCheck {
+            region,
+            outcomes,
+            error,
+            replay,
+        }
+    }
+
+    fn official(&self) -> Vec<Count> {
+        self.region.outcome.iter().map(|x| x.count()).collect()
+    }
+
+    /// The probability of the official outcome
+    fn probability(&self) -> Option<Fraction> {
+        let official = self.official();
+        self.outcomes
+            .iter()
+            .find(|(seats, _)| *seats == official)
+            .map(|(_, probability)| *probability)
+    }
+
+    fn verdict(&self) -> Verdict {
+        match (&self.error, self.probability(), &self.replay) {
+            (Some(_), _, _) => Verdict::Error,
+            (None, None, _) => Verdict::Fail,
+            (None, Some(_), Some(Ok(seats))) if *seats == self.official() => Verdict::Drawn,
+            (None, Some(_), Some(_)) => Verdict::Fail,
+            (None, Some(_), None) if self.outcomes.len() > 1 => Verdict::LotDependent,
+            (None, Some(_), None) => Verdict::Pass,
+        }
+    }
 
-        let records = records.chunk_by(|x, y| x[1] == y[1]).map(|record| {
-            (
-                &record[0][0],
-                record
+    /// Why the official outcome was not reproduced, if it was not
+    fn problem(&self) -> Option<String> {
+        let seats = |seats: &[Count]| {
+            seats
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match (self.verdict(), &self.replay) {
+            (Verdict::Pass | Verdict::Drawn | Verdict::LotDependent, _) => None,
+            (Verdict::Fail, Some(Err(problem))) if self.probability().is_some() => {
+                Some(problem.clone())
+            }
+            (Verdict::Fail, Some(Ok(replayed))) if self.probability().is_some() => Some(format!(
+                "expected {}, the official lots give {}",
+                seats(&self.official()),
+                seats(replayed)
+            )),
+            (Verdict::Fail, _) => Some(format!(
+                "expected {}, computed {}",
+                seats(&self.official()),
+                self.outcomes
                     .iter()
-                    .filter_map(|x| {
-                        (!ignored(&x[2])).then_some(Votes(x[4].parse().unwrap_or_default()))
+                    .map(|(outcome, _)| seats(outcome))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            )),
+            (Verdict::Error, _) => self
+                .error
+                .as_ref()
+                .map(|err| format!("cannot apportion the seats: {err}")),
+        }
+    }
+}
+
+/// The validation of all regions in a number of files
+#[cfg(feature = "validate")]
+#[derive(Debug)]
+struct ValidationResults {
+    /// The regions in every file, or why the file could not be read
+    files: Vec<(PathBuf, Result<Vec<RegionCheck>, String>)>,
+}
+
+#[cfg(feature = "validate")]
+impl ValidationResults {
+    fn regions(&self) -> impl Iterator<Item = (&PathBuf, &RegionCheck)> {
+        self.files
+            .iter()
+            .filter_map(|(file, checks)| Some((file, checks.as_ref().ok()?)))
+            .flat_map(|(file, checks)| checks.iter().map(move |check| (file, check)))
+    }
+
+    fn count(&self, verdict: Verdict) -> usize {
+        self.regions()
+            .filter(|(_, check)| check.verdict() == verdict)
+            .count()
+    }
+
+    fn unreadable(&self) -> impl Iterator<Item = (&PathBuf, &String)> {
+        self.files
+            .iter()
+            .filter_map(|(file, checks)| Some((file, checks.as_ref().err()?)))
+    }
+
+    fn is_valid(&self) -> bool {
+        self.unreadable().next().is_none()
+            && self.regions().all(|(_, check)| check.verdict().is_valid())
+    }
+
+    /// The exit code of `validate`, which is an error if an official outcome was not reproduced
+    fn exit_code(&self) -> i32 {
+        if self.is_valid() { 0 } else { 1 }
+    }
+}
+
+/// Check the official outcome of every region in the `data_sources`
+#[cfg(feature = "validate")]
+fn validate(data_sources: &[PathBuf], rules: Rules) -> ValidationResults {
+    ValidationResults {
+        files: data_sources
+            .iter()
+            .map(|data_source| {
+                let checks = try_read_regions(data_source).map(|regions| {
+                    regions
+                        .into_iter()
+                        .map(|region| RegionCheck::new(region, rules))
+                        .collect()
+                });
+                (data_source.clone(), checks)
+            })
+            .collect(),
+    }
+}
+
+#[cfg(feature = "validate")]
+fn print_validation(results: &ValidationResults) {
+    for (file, checks) in &results.files {
+        let checks = match checks {
+            Ok(checks) => checks,
+            Err(err) => {
+                println!("ERROR {}: {err}", file.display());
+                continue;
+            }
+        };
+        for check in checks {
+            let name = format!("{}:{}", file.display(), check.region.id);
+            match (check.verdict(), check.probability(), check.problem()) {
+                (Verdict::Drawn, _, _) => {
+                    println!("pass  {name} (the outcome was decided by drawing lots)");
+                    for draw in &check.region.draws {
+                        println!("      {}", check.region.describe_draw(draw));
+                    }
+                }
+                (Verdict::LotDependent, Some(probability), _) => println!(
+                    "LOTS  {name}: the outcome depended on drawing lots, but the official lots are \
+                     not recorded (probability: {probability})"
+                ),
+                (Verdict::Fail, _, Some(problem)) => println!("FAIL  {name}: {problem}"),
+                (Verdict::Error, _, Some(problem)) => println!("ERROR {name}: {problem}"),
+                _ => println!("pass  {name}"),
+            }
+        }
+    }
+
+    let unreadable = results.unreadable().count();
+    println!(
+        "{} regions: {} passed, {} decided by the official lots, {} depended on unrecorded lots, {} failed, \
+         {} could not be apportioned{}",
+        results.regions().count(),
+        results.count(Verdict::Pass),
+        results.count(Verdict::Drawn),
+        results.count(Verdict::LotDependent),
+        results.count(Verdict::Fail),
+        results.count(Verdict::Error),
+        match unreadable {
+            0 => String::new(),
+            1 => ", 1 file could not be read".to_string(),
+            n => format!(", {n} files could not be read"),
+        }
+    );
+}
+
+/// Print every correction that was applied to the data, with its source
+#[cfg(feature = "validate")]
+fn print_corrections(results: &ValidationResults) {
+    let corrections = results
+        .regions()
+        .flat_map(|(file, check)| std::iter::repeat(file).zip(&check.region.corrections))
+        .collect::<Vec<_>>();
+    if corrections.is_empty() {
+        println!("No corrections are in effect.");
+        return;
+    }
+    println!("Corrections in effect:");
+    for (file, correction) in corrections {
+        println!("  {}: {correction}", file.display());
+    }
+}
+
+#[cfg(feature = "validate")]
+fn write_report(path: &Path, report: &str) {
+    if let Err(err) = std::fs::write(path, report) {
+        eprintln!("cannot write {}: {err}", path.display());
+        std::process::exit(1)
+    }
+}
+
+/// Escape text for use in XML content and attribute values
+#[cfg(feature = "validate")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The validation as a JUnit XML report: every file is a test suite, and every region in it a test
+/// case. A file that could not be read is a test suite with a single erroneous test case.
+#[cfg(feature = "validate")]
+fn validation_junit(results: &ValidationResults) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let failures = results.count(Verdict::Fail);
+    let errors = results.count(Verdict::Error) + results.unreadable().count();
+    let tests = results.regions().count() + results.unreadable().count();
+    xml += &format!(
+        "<testsuites name=\"validate\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\">\n"
+    );
+
+    for (file, checks) in &results.files {
+        let suite = xml_escape(&file.display().to_string());
+        let checks = match checks {
+            Ok(checks) => checks,
+            Err(err) => {
+                xml += &format!(
+                    "  <testsuite name=\"{suite}\" tests=\"1\" failures=\"0\" errors=\"1\">\n    \
+                     <testcase classname=\"{suite}\" name=\"{suite}\">\n      \
+                     <error message=\"{}\"/>\n    </testcase>\n  </testsuite>\n",
+                    xml_escape(err)
+                );
+                continue;
+            }
+        };
+        let count = |verdict| checks.iter().filter(|x| x.verdict() == verdict).count();
+        xml += &format!(
+            "  <testsuite name=\"{suite}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+            checks.len(),
+            count(Verdict::Fail),
+            count(Verdict::Error)
+        );
+        for check in checks {
+            xml += &format!(
+                "    <testcase classname=\"{suite}\" name=\"{}\">\n",
+                xml_escape(&check.region.id)
+            );
+            match (check.verdict(), check.probability(), check.problem()) {
+                (Verdict::Drawn, _, _) => {
+                    let draws = check
+                        .region
+                        .draws
+                        .iter()
+                        .map(|draw| check.region.describe_draw(draw))
+                        .collect::<Vec<_>>();
+                    xml += &format!(
+                        "      <system-out>the outcome was decided by drawing lots: {}</system-out>\n",
+                        xml_escape(&draws.join("; "))
+                    )
+                }
+                (Verdict::LotDependent, Some(probability), _) => {
+                    xml += &format!(
+                        "      <system-out>the outcome depended on drawing lots, but the official \
+                         lots are not recorded, probability: {}/{}</system-out>\n",
+                        probability.numerator(),
+                        probability.denominator()
+                    )
+                }
+                (Verdict::Fail, _, Some(problem)) => {
+                    xml += &format!("      <failure message=\"{}\"/>\n", xml_escape(&problem))
+                }
+                (Verdict::Error, _, Some(problem)) => {
+                    xml += &format!("      <error message=\"{}\"/>\n", xml_escape(&problem))
+                }
+                _ => {}
+            }
+            xml += "    </testcase>\n";
+        }
+        xml += "  </testsuite>\n";
+    }
+
+    xml += "</testsuites>\n";
+    xml
+}
+
+/// The validation of a single region, in machine-readable form
+#[cfg(all(feature = "validate", feature = "json"))]
+#[derive(serde::Serialize)]
+struct Validation {
+    file: PathBuf,
+    region: String,
+    method: Method,
+    /// The official outcome
+    parties: Vec<PartySeats>,
+    /// Every possible outcome
+    outcomes: Vec<PossibleOutcome>,
+    valid: bool,
+    verdict: Verdict,
+    /// The corrections that were applied to the official data
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    corrections: Vec<String>,
+    /// The lots that were officially drawn
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    draws: Vec<String>,
+    /// Why the seats could not be apportioned, if that is the case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[cfg(all(feature = "validate", feature = "json"))]
+#[derive(serde::Serialize)]
+struct PossibleOutcome {
+    seats: Vec<Count>,
+    probability: Fraction,
+}
+
+/// A file that could not be validated, in machine-readable form
+#[cfg(all(feature = "validate", feature = "json"))]
+#[derive(serde::Serialize)]
+struct UnreadableFile {
+    file: PathBuf,
+    error: String,
+}
+
+/// The validation of all regions, in machine-readable form
+#[cfg(all(feature = "validate", feature = "json"))]
+#[derive(serde::Serialize)]
+struct ValidationReport {
+    passed: usize,
+    drawn: usize,
+    lot_dependent: usize,
+    failed: usize,
+    errors: usize,
+    regions: Vec<Validation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unreadable: Vec<UnreadableFile>,
+}
+
+#[cfg(all(feature = "validate", feature = "json"))]
+fn validation_json(results: &ValidationResults) -> String {
+    let regions = results
+        .regions()
+        .map(|(file, check)| {
+            let region = &check.region;
+            Validation {
+                file: file.clone(),
+                region: region.id.clone(),
+                method: region.method(),
+                parties: (0..region.votes.len())
+                    .map(|i| PartySeats {
+                        list: i + 1,
+                        name: region.names[i].clone(),
+                        votes: region.votes[i],
+                        seats: {
+                            let mut seats = region.outcome[i];
+                            seats.limit = region.candidates[i].limit;
+                            seats
+                        },
                     })
-                    .collect::<Vec<_>>(),
-                record
+                    .collect(),
+                valid: check.verdict().is_valid(),
+                verdict: check.verdict(),
+                corrections: region.corrections.iter().map(|x| x.to_string()).collect(),
+                draws: region
+                    .draws
                     .iter()
-                    .filter_map(|x| {
-                        (!ignored(&x[2])).then_some(Seats::filled(x[5].parse().unwrap_or_default()))
-                    })
-                    .collect::<Vec<_>>(),
-                record
+                    .map(|draw| region.describe_draw(draw))
+                    .collect(),
+                error: check.error.as_ref().map(|err| err.to_string()),
+                outcomes: check
+                    .outcomes
                     .iter()
-                    .filter_map(|x| {
-                        (!ignored(&x[2])).then_some(
-                            x[6].parse()
-                                .map(Seats::limited)
-                                .unwrap_or(Seats::unlimited()),
-                        )
+                    .map(|(seats, probability)| PossibleOutcome {
+                        seats: seats.clone(),
+                        probability: *probability,
                     })
-                    .collect::<Vec<_>>(),
-            )
-        });
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let report = ValidationReport {
+        passed: results.count(Verdict::Pass),
+        drawn: results.count(Verdict::Drawn),
+        lot_dependent: results.count(Verdict::LotDependent),
+        failed: results.count(Verdict::Fail),
+        errors: results.count(Verdict::Error) + results.unreadable().count(),
+        regions,
+        unreadable: results
+            .unreadable()
+            .map(|(file, error)| UnreadableFile {
+                file: file.clone(),
+                error: error.clone(),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&report).unwrap()
+}
+
+/// Divide the seats over the lists of every party, based on the votes in every kieskring
+#[cfg(feature = "validate")]
+fn party_lists(args: &ListsArgs, rules: Rules) {
+    let text = std::fs::read_to_string(&args.file).unwrap_or_else(|err| {
+        eprintln!("cannot read {}: {err}", args.file.display());
+        std::process::exit(1)
+    });
+    let CsvPartyLists {
+        lists,
+        parties,
+        votes,
+        mut seats,
+        ..
+    } = read_party_lists_csv(&text).unwrap_or_else(|err| {
+        eprintln!("{}: {err}", args.file.display());
+        std::process::exit(1)
+    });
 
-        for (id, votes, outcome, candidates) in records {
-            #[cfg(feature = "rand-validate")]
-            let (votes, outcome, candidates) = {
-                let (mut votes, mut outcome, mut candidates) = (votes, outcome, candidates);
-                use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
-                let rng = StdRng::from_os_rng();
-                votes.shuffle(&mut rng.clone());
-                outcome.shuffle(&mut rng.clone());
-                candidates.shuffle(&mut rng.clone());
-
-                (votes, outcome, candidates)
+    let mut ballot = args.lots.ballot();
+    let mut trace = Vec::new();
+    apportioned(allocate_party_lists(
+        Seats::filled(args.seats),
+        &votes,
+        &mut seats,
+        &parties,
+        &mut Context::observed(&mut ballot, &mut trace).with_rules(rules),
+    ));
+    let labels = lists
+        .iter()
+        .enumerate()
+        .map(|(i, (party, list))| format!("{} ({party}, {list})", i + 1))
+        .collect::<Vec<_>>();
+    for event in trace {
+        if args.trace || matches!(event, Event::Lot { .. }) {
+            println!("{}", event.describe(&labels));
+        }
+    }
+    for ((party, list), seats) in std::iter::zip(lists, seats) {
+        println!("{party}, {list}: {seats}");
+    }
+}
+
+/// Report all elections in which a seat was decided by drawing lots. A file or region that cannot
+/// be processed is reported, after which the other regions are still examined.
+#[cfg(feature = "validate")]
+fn lots(data_sources: &Vec<PathBuf>, rules: Rules) {
+    let mut failed = false;
+    for data_source in data_sources {
+        let regions = match try_read_regions(data_source) {
+            Ok(regions) => regions,
+            Err(err) => {
+                println!("ERROR {}: {err}", data_source.display());
+                failed = true;
+                continue;
+            }
+        };
+        for region in regions {
+            let outcomes = match region.possible_outcomes(rules) {
+                Ok(outcomes) => outcomes,
+                Err(err) => {
+                    println!(
+                        "ERROR {}:{}: cannot apportion the seats: {err}",
+                        data_source.display(),
+                        region.id
+                    );
+                    failed = true;
+                    continue;
+                }
             };
-            let total_seats = outcome.iter().map(|x| x.count()).sum();
-            println!("checking {}:{id}", data_source.display());
-
-            let file_name = data_source.file_name().unwrap().to_string_lossy();
-
-            // Due to drawing of lots, as happened in GR 2026 at Roermond, there can be multiple
-            // correct outcomes of an election.
-            #[allow(clippy::never_loop)]
-            loop {
-                #[cfg(feature = "loop-validate")]
-                let candidates = candidates.clone();
-
-                let mut seats = candidates;
-
-                if file_name.starts_with("uitslag_TK") || file_name.starts_with("uitslag_EP") {
-                    match &file_name[10..14] {
-                        "1918" => allocate_1918(Seats::filled(total_seats), &votes, &mut seats),
-                        "1922" => allocate_1922(Seats::filled(total_seats), &votes, &mut seats),
-                        "1925" | "1929" | "1933" => {
-                            allocate_bongaerts(Seats::filled(total_seats), &votes, &mut seats)
-                        }
-                        _ => allocate_national(Seats::filled(total_seats), &votes, &mut seats),
-                    }
+            if outcomes.len() <= 1 {
+                continue;
+            }
+
+            let outcome = region.outcome.iter().map(|x| x.count()).collect::<Vec<_>>();
+            println!("{}:{}", data_source.display(), region.id);
+            for (seats, probability) in outcomes {
+                let official = if seats == outcome {
+                    " (official outcome)"
                 } else {
-                    allocate(Seats::filled(total_seats), &votes, &mut seats);
-                }
+                    ""
+                };
+                let seats = seats.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                println!(
+                    "  {} with probability {probability}{official}",
+                    seats.join(", ")
+                );
+            }
+        }
+    }
 
-                let seats = seats.iter().map(|x| x.count()).collect::<Vec<_>>();
-                let outcome = outcome.iter().map(|x| x.count()).collect::<Vec<_>>();
+    if failed {
+        std::process::exit(1);
+    }
+}
 
-                #[cfg(feature = "loop-validate")]
-                if seats != outcome {
-                    // this will cause non-termination as an error condition
-                    continue;
+/// Compare the literal text of the Kieswet with the reading of the Kiesraad on the elections in the
+/// data sources and on generated elections, and report every election where the seats differ
+#[cfg(feature = "validate")]
+fn diverge(data_sources: &Vec<PathBuf>, random: usize, seed: Option<u64>) {
+    use rand::SeedableRng;
+
+    let rules = [
+        Interpretation::Literal.rules(),
+        Interpretation::Kiesraad.rules(),
+    ];
+    let mut regions = 0;
+    let mut diverging = 0;
+
+    for data_source in data_sources {
+        for region in read_regions(data_source) {
+            regions += 1;
+            let election = region.election();
+            if let Some(divergence) = find_divergence(&election, rules) {
+                diverging += 1;
+                println!("{}:{}", data_source.display(), region.id);
+                print_divergence(&election, &divergence);
+                let minimal = minimize(&election, rules);
+                println!("minimal example: {}", describe_election(&minimal));
+                if let Some(divergence) = find_divergence(&minimal, rules) {
+                    print_divergence(&minimal, &divergence);
                 }
+                println!();
+            }
+        }
+    }
+
+    let mut rng = match seed {
+        Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+        None => rand::rngs::StdRng::from_os_rng(),
+    };
+    // generated elections are grouped by the minimal example they reduce to
+    let mut examples = Vec::<(Election, Election, usize)>::new();
+    for _ in 0..random {
+        let election = adversarial_election(&mut rng);
+        if find_divergence(&election, rules).is_none() {
+            continue;
+        }
+        let minimal = minimize(&election, rules);
+        let description = describe_election(&minimal);
+        match examples
+            .iter_mut()
+            .find(|(example, _, _)| describe_election(example) == description)
+        {
+            Some((_, _, count)) => *count += 1,
+            None => examples.push((minimal, election, 1)),
+        }
+    }
+
+    let generated = examples.iter().map(|(_, _, count)| count).sum::<usize>();
+    for (minimal, election, count) in &examples {
+        println!(
+            "{count} generated elections reduce to: {}",
+            describe_election(minimal)
+        );
+        if let Some(divergence) = find_divergence(minimal, rules) {
+            print_divergence(minimal, &divergence);
+        }
+        println!("for instance: {}", describe_election(election));
+        println!();
+    }
+
+    println!(
+        "{diverging} of {regions} regions and {generated} of {random} generated elections diverge"
+    );
+}
+
+/// Generate a small council election in which a party has an absolute majority of the votes and
+/// lists may be exhausted, which is where the interpretations of the Kieswet differ
+#[cfg(feature = "validate")]
+fn adversarial_election(rng: &mut impl rand::Rng) -> Election {
+    let seats: Count = rng.random_range(3..19);
+    let parties = rng.random_range(2..7);
+    let majority = rng.random_range(0..parties);
+
+    let mut votes = (0..parties)
+        .map(|_| {
+            if rng.random_bool(0.2) {
+                0
+            } else {
+                rng.random_range(1..200)
+            }
+        })
+        .collect::<Vec<Count>>();
+    let others = votes.iter().sum::<Count>() - votes[majority];
+    votes[majority] = others + rng.random_range(1..=others.max(1));
+
+    let candidates = (0..parties)
+        .map(|i| {
+            if i == majority {
+                let half = seats / 2;
+                Some(rng.random_range(half.saturating_sub(1)..=half + 1))
+            } else {
+                rng.random_bool(0.5)
+                    .then(|| rng.random_range(0..=seats / 2 + 1))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Election {
+        body: None,
+        seats,
+        method: Method::for_seats(seats),
+        parties: std::iter::zip(votes, candidates)
+            .map(|(votes, candidates)| Party {
+                name: String::new(),
+                list: None,
+                votes: Votes(votes),
+                candidates,
+            })
+            .collect(),
+        combinations: Vec::new(),
+        rules: Rules::default(),
+        threshold: None,
+    }
+}
+
+/// A one-line description of an election
+#[cfg(feature = "validate")]
+fn describe_election(election: &Election) -> String {
+    let votes = election
+        .parties
+        .iter()
+        .map(|x| x.votes.0.to_string())
+        .collect::<Vec<_>>();
+    let candidates = election
+        .parties
+        .iter()
+        .map(|x| x.candidates.map_or("-".to_string(), |n| n.to_string()))
+        .collect::<Vec<_>>();
+    format!(
+        "{} seats, votes {}, candidates {}",
+        election.seats,
+        votes.join(" "),
+        candidates.join(" ")
+    )
+}
+
+/// Show the seats under both interpretations, and the steps taken after they split
+#[cfg(feature = "validate")]
+fn print_divergence(election: &Election, divergence: &Divergence) {
+    let names = ["literal", "kiesraad"];
+    for (name, seats) in std::iter::zip(names, &divergence.seats) {
+        match seats {
+            Ok(seats) => {
+                let seats = seats.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                println!("  {name:8} {}", seats.join(", "));
+            }
+            Err(err) => println!("  {name:8} cannot apportion the seats: {err}"),
+        }
+    }
+    println!(
+        "  the {} apportionment splits after {} steps:",
+        election.method, divergence.split
+    );
+    let labels = election.labels();
+    for (name, trace) in std::iter::zip(names, &divergence.traces) {
+        for event in &trace[divergence.split..] {
+            println!("  {name:8} {}", event.describe(&labels));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "validate"))]
+mod tests {
+    use super::*;
+
+    fn region(id: &str, votes: &[Count], official: &[Count]) -> Region {
+        Region {
+            election: "GR20260318_Gemeente".to_string(),
+            id: id.to_string(),
+            names: ["A & B", "C", "D"][..votes.len()]
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            votes: votes.iter().map(|&x| Votes(x)).collect(),
+            outcome: official.iter().map(|&x| Seats::filled(x)).collect(),
+            candidates: vec![Seats::unlimited(); votes.len()],
+            combinations: Vec::new(),
+            corrections: Vec::new(),
+            draws: Vec::new(),
+        }
+    }
+
+    fn check(
+        region: Region,
+        outcomes: &[(&[Count], Fraction)],
+        error: Option<AllocationError>,
+        replay: Option<Result<Vec<Count>, String>>,
+    ) -> RegionCheck {
+        RegionCheck {
+            region,
+            outcomes: outcomes
+                .iter()
+                .map(|(seats, probability)| (seats.to_vec(), *probability))
+                .collect(),
+            error,
+            replay,
+        }
+    }
+
+    /// A validation of one region with every verdict, and a file that could not be read
+    fn results() -> ValidationResults {
+        let half = frac(1, 2);
+        let mut drawn = region("Drawn", &[10, 10], &[0, 1]);
+        drawn.draws.push(OfficialDraw {
+            tied: vec![0, 1],
+            winner: 1,
+            source: "Proces-verbaal <p. 3>".to_string(),
+        });
+        ValidationResults {
+            files: vec![
+                (
+                    PathBuf::from("uitslag.csv"),
+                    Ok(vec![
+                        check(
+                            region("Pass", &[20, 10], &[2, 1]),
+                            &[(&[2, 1], frac(1, 1))],
+                            None,
+                            None,
+                        ),
+                        check(
+                            drawn,
+                            &[(&[1, 0], half), (&[0, 1], half)],
+                            None,
+                            Some(Ok(vec![0, 1])),
+                        ),
+                        check(
+                            region("Lots", &[10, 10], &[1, 0]),
+                            &[(&[1, 0], half), (&[0, 1], half)],
+                            None,
+                            None,
+                        ),
+                        check(
+                            region("Fail", &[20, 10], &[1, 2]),
+                            &[(&[2, 1], frac(1, 1))],
+                            None,
+                            None,
+                        ),
+                        check(
+                            region("Error", &[0, 0], &[1, 0]),
+                            &[],
+                            Some(AllocationError::ZeroVotes),
+                            None,
+                        ),
+                    ]),
+                ),
+                (
+                    PathBuf::from("missing.csv"),
+                    Err("cannot read missing.csv".to_string()),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn verdicts() {
+        let half = frac(1, 2);
+        let lots: &[(&[Count], Fraction)] = &[(&[1, 0], half), (&[0, 1], half)];
+        let tied = || region("Tied", &[10, 10], &[1, 0]);
+
+        let pass = check(
+            region("Pass", &[20, 10], &[2, 1]),
+            &[(&[2, 1], frac(1, 1))],
+            None,
+            None,
+        );
+        assert_eq!(pass.verdict(), Verdict::Pass);
+        assert_eq!(pass.problem(), None);
+
+        let lot_dependent = check(tied(), lots, None, None);
+        assert_eq!(lot_dependent.verdict(), Verdict::LotDependent);
+        assert_eq!(lot_dependent.probability(), Some(half));
+        assert_eq!(lot_dependent.problem(), None);
+
+        let drawn = check(tied(), lots, None, Some(Ok(vec![1, 0])));
+        assert_eq!(drawn.verdict(), Verdict::Drawn);
+        assert_eq!(drawn.problem(), None);
+
+        let other_lot = check(tied(), lots, None, Some(Ok(vec![0, 1])));
+        assert_eq!(other_lot.verdict(), Verdict::Fail);
+        assert_eq!(
+            other_lot.problem().unwrap(),
+            "expected 1, 0, the official lots give 0, 1"
+        );
+
+        let unused_lot = "the official lots include 1 that were not needed".to_string();
+        let unused_lot = check(tied(), lots, None, Some(Err(unused_lot)));
+        assert_eq!(unused_lot.verdict(), Verdict::Fail);
+        assert_eq!(
+            unused_lot.problem().unwrap(),
+            "the official lots include 1 that were not needed"
+        );
+
+        let fail = check(
+            region("Fail", &[20, 10], &[1, 2]),
+            &[(&[2, 1], frac(1, 1))],
+            None,
+            None,
+        );
+        assert_eq!(fail.verdict(), Verdict::Fail);
+        assert_eq!(fail.problem().unwrap(), "expected 1, 2, computed 2, 1");
 
-                assert_eq!(seats, outcome,);
+        let error = check(
+            region("Error", &[0, 0], &[1, 0]),
+            &[],
+            Some(AllocationError::ZeroVotes),
+            None,
+        );
+        assert_eq!(error.verdict(), Verdict::Error);
+        assert_eq!(
+            error.problem().unwrap(),
+            "cannot apportion the seats: no votes were cast"
+        );
+
+        for verdict in [Verdict::Pass, Verdict::Drawn, Verdict::LotDependent] {
+            assert!(verdict.is_valid(), "{verdict:?}");
+        }
+        for verdict in [Verdict::Fail, Verdict::Error] {
+            assert!(!verdict.is_valid(), "{verdict:?}");
+        }
+    }
+
+    #[test]
+    fn computed_verdicts() {
+        let verdict = |region| RegionCheck::new(region, Rules::default()).verdict();
+        assert_eq!(verdict(region("Pass", &[20, 10], &[2, 1])), Verdict::Pass);
+        assert_eq!(
+            verdict(region("Lots", &[10, 10], &[1, 0])),
+            Verdict::LotDependent
+        );
+        assert_eq!(verdict(region("Fail", &[20, 10], &[1, 2])), Verdict::Fail);
+        assert_eq!(verdict(region("Error", &[0, 0], &[1, 0])), Verdict::Error);
+
+        let mut drawn = region("Drawn", &[10, 10], &[0, 1]);
+        drawn.draws.push(OfficialDraw {
+            tied: vec![0, 1],
+            winner: 1,
+            source: "p. 3".to_string(),
+        });
+        assert_eq!(verdict(drawn.clone()), Verdict::Drawn);
+        drawn.draws[0].winner = 0;
+        assert_eq!(verdict(drawn), Verdict::Fail);
+    }
+
+    #[test]
+    fn exit_codes() {
+        let mut results = results();
+        assert_eq!(results.exit_code(), 1);
+
+        // without the file that could not be read, the failure and the error remain
+        results.files.pop();
+        assert_eq!(results.exit_code(), 1);
+        results.files[0].1.as_mut().unwrap().pop();
+        assert_eq!(results.exit_code(), 1);
+        results.files[0].1.as_mut().unwrap().pop();
+        // an outcome that depended on unrecorded lots is not an error
+        assert_eq!(results.exit_code(), 0);
+        results.files[0].1.as_mut().unwrap().clear();
+        assert_eq!(results.exit_code(), 0);
+    }
 
-                break;
+    #[test]
+    fn junit_report() {
+        assert_eq!(
+            validation_junit(&results()),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="validate" tests="6" failures="1" errors="2">
+  <testsuite name="uitslag.csv" tests="5" failures="1" errors="1">
+    <testcase classname="uitslag.csv" name="Pass">
+    </testcase>
+    <testcase classname="uitslag.csv" name="Drawn">
+      <system-out>the outcome was decided by drawing lots: C won the lot between A &amp; B and C (Proces-verbaal &lt;p. 3&gt;)</system-out>
+    </testcase>
+    <testcase classname="uitslag.csv" name="Lots">
+      <system-out>the outcome depended on drawing lots, but the official lots are not recorded, probability: 1/2</system-out>
+    </testcase>
+    <testcase classname="uitslag.csv" name="Fail">
+      <failure message="expected 1, 2, computed 2, 1"/>
+    </testcase>
+    <testcase classname="uitslag.csv" name="Error">
+      <error message="cannot apportion the seats: no votes were cast"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="missing.csv" tests="1" failures="0" errors="1">
+    <testcase classname="missing.csv" name="missing.csv">
+      <error message="cannot read missing.csv"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_report() {
+        assert_eq!(
+            validation_json(&results()),
+            r#"{
+  "passed": 1,
+  "drawn": 1,
+  "lot_dependent": 1,
+  "failed": 1,
+  "errors": 2,
+  "regions": [
+    {
+      "file": "uitslag.csv",
+      "region": "Pass",
+      "method": "surpluses",
+      "parties": [
+        {
+          "list": 1,
+          "name": "A & B",
+          "votes": 20,
+          "seats": 2
+        },
+        {
+          "list": 2,
+          "name": "C",
+          "votes": 10,
+          "seats": 1
+        }
+      ],
+      "outcomes": [
+        {
+          "seats": [
+            2,
+            1
+          ],
+          "probability": {
+            "numerator": 1,
+            "denominator": 1
+          }
+        }
+      ],
+      "valid": true,
+      "verdict": "pass"
+    },
+    {
+      "file": "uitslag.csv",
+      "region": "Drawn",
+      "method": "surpluses",
+      "parties": [
+        {
+          "list": 1,
+          "name": "A & B",
+          "votes": 10,
+          "seats": 0
+        },
+        {
+          "list": 2,
+          "name": "C",
+          "votes": 10,
+          "seats": 1
+        }
+      ],
+      "outcomes": [
+        {
+          "seats": [
+            1,
+            0
+          ],
+          "probability": {
+            "numerator": 1,
+            "denominator": 2
+          }
+        },
+        {
+          "seats": [
+            0,
+            1
+          ],
+          "probability": {
+            "numerator": 1,
+            "denominator": 2
+          }
+        }
+      ],
+      "valid": true,
+      "verdict": "drawn",
+      "draws": [
+        "C won the lot between A & B and C (Proces-verbaal <p. 3>)"
+      ]
+    },
+    {
+      "file": "uitslag.csv",
+      "region": "Lots",
+      "method": "surpluses",
+      "parties": [
+        {
+          "list": 1,
+          "name": "A & B",
+          "votes": 10,
+          "seats": 1
+        },
+        {
+          "list": 2,
+          "name": "C",
+          "votes": 10,
+          "seats": 0
+        }
+      ],
+      "outcomes": [
+        {
+          "seats": [
+            1,
+            0
+          ],
+          "probability": {
+            "numerator": 1,
+            "denominator": 2
+          }
+        },
+        {
+          "seats": [
+            0,
+            1
+          ],
+          "probability": {
+            "numerator": 1,
+            "denominator": 2
+          }
+        }
+      ],
+      "valid": true,
+      "verdict": "lot_dependent"
+    },
+    {
+      "file": "uitslag.csv",
+      "region": "Fail",
+      "method": "surpluses",
+      "parties": [
+        {
+          "list": 1,
+          "name": "A & B",
+          "votes": 20,
+          "seats": 1
+        },
+        {
+          "list": 2,
+          "name": "C",
+          "votes": 10,
+          "seats": 2
+        }
+      ],
+      "outcomes": [
+        {
+          "seats": [
+            2,
+            1
+          ],
+          "probability": {
+            "numerator": 1,
+            "denominator": 1
+          }
+        }
+      ],
+      "valid": false,
+      "verdict": "fail"
+    },
+    {
+      "file": "uitslag.csv",
+      "region": "Error",
+      "method": "surpluses",
+      "parties": [
+        {
+          "list": 1,
+          "name": "A & B",
+          "votes": 0,
+          "seats": 1
+        },
+        {
+          "list": 2,
+          "name": "C",
+          "votes": 0,
+          "seats": 0
+        }
+      ],
+      "outcomes": [],
+      "valid": false,
+      "verdict": "error",
+      "error": "no votes were cast"
+    }
+  ],
+  "unreadable": [
+    {
+      "file": "missing.csv",
+      "error": "cannot read missing.csv"
+    }
+  ]
+}"#
+        );
+    }
+
+    /// Read a data file with an errata file and a lots file next to it, in a fresh directory
+    fn read_with(name: &str, data: &str, errata: &str, lots: &str) -> Result<Vec<Region>, String> {
+        let dir =
+            std::env::temp_dir().join(format!("kiesraad-model-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data_source = dir.join("uitslag_GR20260318_Gemeente.csv");
+        std::fs::write(&data_source, data).unwrap();
+        for (extension, text) in [("errata", errata), ("loting", lots)] {
+            if !text.is_empty() {
+                std::fs::write(data_source.with_extension(extension), text).unwrap();
             }
         }
+        let result = try_read_regions(&data_source)
+            .map_err(|err| err.replace(&dir.display().to_string(), "DIR"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        result
+    }
+
+    const DATA: &str = "Gemeente;Code;AmsterdamseCode;Partij;AantalStemmen;AantalZetels\n\
+                        Vaals;G0981;;VVD;2100;3\n\
+                        Vaals;G0981;;CDA;1900;2\n";
+
+    #[test]
+    fn corrections_and_lots_next_to_the_data() {
+        let regions = read_with(
+            "valid",
+            DATA,
+            "Regio;Partij;Kolom;Waarde;Bron\nVaals;CDA;AantalStemmen;1901;p. 2\n",
+            "Regio;Partijen;Winnaar;Bron\nG0981;VVD|CDA;CDA;p. 3\n",
+        )
+        .unwrap();
+        assert_eq!(regions[0].votes, [Votes(2100), Votes(1901)]);
+        assert_eq!(regions[0].corrections.len(), 1);
+        assert_eq!(regions[0].draws[0].winner, 1);
+    }
+
+    #[test]
+    fn errors_in_files_next_to_the_data() {
+        let data = format!("{DATA}Vaals;G0981;;PvdA;veel;1\n");
+        let result = read_with("data", &data, "", "");
+        assert_eq!(
+            result.err().unwrap(),
+            "line 4: AantalStemmen is not a number: veel"
+        );
+
+        let unknown_region = "Regio;Partij;Kolom;Waarde;Bron\nValkenburg;CDA;AantalZetels;2;p. 2\n";
+        let result = read_with("region", DATA, unknown_region, "");
+        assert_eq!(
+            result.err().unwrap(),
+            "DIR/uitslag_GR20260318_Gemeente.errata: line 2: no region Valkenburg"
+        );
+
+        let unknown_party = "Regio;Partijen;Winnaar;Bron\nVaals;VVD|D66;VVD;p. 3\n";
+        let result = read_with("party", DATA, "", unknown_party);
+        assert_eq!(
+            result.err().unwrap(),
+            "DIR/uitslag_GR20260318_Gemeente.loting: line 2: no list D66"
+        );
     }
 }
@@ -0,0 +1,141 @@
+use crate::AllocationError;
+use crate::data::*;
+
+/// The number of votes that a whole seat is worth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Quota {
+    /// The votes divided by the seats (the Dutch "kiesdeler")
+    Hare,
+    /// The votes divided by one more than the seats, rounded down, plus one
+    Droop,
+    /// The votes divided by one more than the seats
+    HagenbachBischoff,
+    /// The votes divided by two more than the seats
+    Imperiali,
+}
+
+impl Quota {
+    /// The quota in an election with the given total number of votes and seats
+    pub fn of(self, votes: Count, seats: Count) -> Result<Fraction, AllocationError> {
+        let more_seats = |n| seats.checked_add(n).ok_or(AllocationError::Overflow);
+        match self {
+            Quota::Hare => Fraction::new(votes, seats),
+            Quota::Droop => Ok(frac(votes / more_seats(1)? + 1, 1)),
+            Quota::HagenbachBischoff => Fraction::new(votes, more_seats(1)?),
+            Quota::Imperiali => Fraction::new(votes, more_seats(2)?),
+        }
+    }
+}
+
+impl std::fmt::Display for Quota {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Quota::Hare => write!(f, "Hare"),
+            Quota::Droop => write!(f, "Droop"),
+            Quota::HagenbachBischoff => write!(f, "Hagenbach-Bischoff"),
+            Quota::Imperiali => write!(f, "Imperiali"),
+        }
+    }
+}
+
+/// The condition for a party to qualify for surplus seats, as a fraction of the quota
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SurplusThreshold {
+    /// The votes of the party are at least this fraction of the quota
+    Votes(Fraction),
+    /// The votes of the party per seat, counting its whole seats and its first surplus seat, are
+    /// at least this fraction of the quota
+    VotesPerSeat(Fraction),
+}
+
+/// How the seats are apportioned that remain after the rounds of largest surpluses
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Fallback {
+    /// The seats remain unoccupied
+    Unoccupied,
+    /// By largest averages, where every party can receive one seat more than it could in the
+    /// rounds of largest surpluses
+    Averages,
+    /// By largest averages, without restrictions
+    UnrestrictedAverages,
+    /// By largest surpluses, among the parties that do not qualify for surplus seats
+    BelowThreshold,
+}
+
+/// A largest surpluses (or largest remainders) method: every party receives a seat for every
+/// whole quota, and then the parties that qualify receive surplus seats in order of their
+/// surplus, one per party in every round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuotaMethod {
+    pub quota: Quota,
+    /// The condition for surplus seats; without one, every party qualifies
+    pub threshold: Option<SurplusThreshold>,
+    /// The number of rounds of largest surpluses, i.e. the number of surplus seats a party can
+    /// receive
+    pub surplus_cap: Count,
+    pub fallback: Fallback,
+}
+
+const THREE_QUARTERS: Fraction = Fraction {
+    numerator: 3,
+    denominator: 4,
+};
+
+impl QuotaMethod {
+    /// The method for Dutch bodies of less than 19 seats, without the absolute majority correction
+    /// (see `allocate_per_surplus`)
+    pub const DUTCH: QuotaMethod = QuotaMethod {
+        quota: Quota::Hare,
+        threshold: Some(SurplusThreshold::Votes(THREE_QUARTERS)),
+        surplus_cap: 1,
+        fallback: Fallback::Averages,
+    };
+
+    /// The method for Dutch bodies of less than 19 seats until 2017, where the averages round was
+    /// not restricted
+    pub const DUTCH_BEFORE_2017: QuotaMethod = QuotaMethod {
+        fallback: Fallback::UnrestrictedAverages,
+        ..QuotaMethod::DUTCH
+    };
+
+    /// The method used from 1925 until 1933, proposed by Bongaerts in 1922: a party needs 75% of
+    /// a whole seat *on average*. Bongaerts seems to have proposed straight Sainte-Laguë instead of
+    /// the averages round.
+    pub const BONGAERTS: QuotaMethod = QuotaMethod {
+        quota: Quota::Hare,
+        threshold: Some(SurplusThreshold::VotesPerSeat(THREE_QUARTERS)),
+        surplus_cap: 1,
+        fallback: Fallback::Averages,
+    };
+
+    /// The method used in 1918, with a threshold of half a quota (see `allocate_archaic`); this is
+    /// my best interpretation from a 1917 law
+    pub const ARCHAIC_1918: QuotaMethod = QuotaMethod {
+        quota: Quota::Hare,
+        threshold: Some(SurplusThreshold::Votes(Fraction {
+            numerator: 1,
+            denominator: 2,
+        })),
+        surplus_cap: 2,
+        fallback: Fallback::BelowThreshold,
+    };
+
+    /// The method used in 1922, with a threshold of 75% of a quota
+    pub const ARCHAIC_1922: QuotaMethod = QuotaMethod {
+        threshold: Some(SurplusThreshold::Votes(THREE_QUARTERS)),
+        ..QuotaMethod::ARCHAIC_1918
+    };
+}
+
+impl std::fmt::Display for QuotaMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "largest surpluses ({} quota)", self.quota)
+    }
+}
@@ -0,0 +1,254 @@
+use crate::data::*;
+use crate::election::Method;
+
+/// The kinds of bodies for which the Kiesraad publishes election results, by the category that
+/// starts the identifier of an election
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Body {
+    /// The House of Representatives ("TK")
+    TweedeKamer,
+    /// The Dutch members of the European Parliament ("EP")
+    EuropeesParlement,
+    /// A provincial council ("PS")
+    ProvincialeStaten,
+    /// A water board ("WS")
+    Waterschap,
+    /// A municipal council ("GR")
+    Gemeenteraad,
+    /// An island council of Bonaire, Sint Eustatius or Saba ("ER")
+    Eilandsraad,
+}
+
+impl Body {
+    /// The body with the given category, e.g. "TK"
+    pub fn from_category(category: &str) -> Option<Body> {
+        match category {
+            "TK" => Some(Body::TweedeKamer),
+            "EP" => Some(Body::EuropeesParlement),
+            "PS" => Some(Body::ProvincialeStaten),
+            "WS" => Some(Body::Waterschap),
+            "GR" => Some(Body::Gemeenteraad),
+            "ER" => Some(Body::Eilandsraad),
+            _ => None,
+        }
+    }
+
+    /// Whether the seats are apportioned nationally, with a threshold of one whole seat, rather
+    /// than by the rules for other bodies (see `Method::for_seats`)
+    pub fn is_national(self) -> bool {
+        matches!(self, Body::TweedeKamer | Body::EuropeesParlement)
+    }
+}
+
+impl std::fmt::Display for Body {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Body::TweedeKamer => write!(f, "Tweede Kamer"),
+            Body::EuropeesParlement => write!(f, "Europees Parlement"),
+            Body::ProvincialeStaten => write!(f, "Provinciale Staten"),
+            Body::Waterschap => write!(f, "waterschap"),
+            Body::Gemeenteraad => write!(f, "gemeenteraad"),
+            Body::Eilandsraad => write!(f, "eilandsraad"),
+        }
+    }
+}
+
+/// The successive rules for apportioning seats in the Kieswet and its predecessors, named after
+/// the first election for the Tweede Kamer in which they were used
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Kieswet {
+    /// Largest surpluses with a threshold of half a quota (see `allocate_1918`)
+    Of1918,
+    /// As in 1918, with a threshold of 75% of a quota (see `allocate_1922`)
+    Of1922,
+    /// Largest surpluses with a threshold of 75% of a quota per seat (see `allocate_bongaerts`)
+    Of1925,
+    /// Largest averages with a threshold of one whole seat (see `allocate_national`)
+    Of1937,
+    /// As in 1937, where lists can form a combination (see `allocate_combined`)
+    Of1977,
+    /// As in 1937; list combinations were abolished, and in bodies of less than 19 seats the seats
+    /// that remain after the largest surpluses are apportioned by restricted averages
+    Of2017,
+}
+
+impl Kieswet {
+    /// The rules in force for an election held in the given year, on the given month and day if
+    /// they are known. The rules apply from the day of the election for the Tweede Kamer after
+    /// which they are named; without a date, an election in that year is taken to be held under
+    /// the new rules.
+    ///
+    /// ```
+    /// use kiesraad_model::*;
+    ///
+    /// assert_eq!(Kieswet::on(2017, Some((3, 14))), Kieswet::Of1977);
+    /// assert_eq!(Kieswet::on(2017, Some((3, 15))), Kieswet::Of2017);
+    /// assert_eq!(Kieswet::on(2017, None), Kieswet::Of2017);
+    /// ```
+    pub fn on(year: u16, date: Option<(u8, u8)>) -> Kieswet {
+        let (month, day) = date.unwrap_or((12, 31));
+        [
+            (Kieswet::Of2017, (2017, 3, 15)),
+            (Kieswet::Of1977, (1977, 5, 25)),
+            (Kieswet::Of1937, (1937, 5, 26)),
+            (Kieswet::Of1925, (1925, 7, 1)),
+            (Kieswet::Of1922, (1922, 7, 5)),
+        ]
+        .into_iter()
+        .find(|(_, first)| (year, month, day) >= *first)
+        .map_or(Kieswet::Of1918, |(kieswet, _)| kieswet)
+    }
+
+    /// The method for a national election under these rules; list combinations are only taken
+    /// into account where the law allowed them
+    pub fn national_method(self, combinations: bool) -> Method {
+        match self {
+            Kieswet::Of1918 => Method::Archaic1918,
+            Kieswet::Of1922 => Method::Archaic1922,
+            Kieswet::Of1925 => Method::Bongaerts,
+            Kieswet::Of1977 if combinations => Method::Combined,
+            Kieswet::Of1937 | Kieswet::Of1977 | Kieswet::Of2017 => Method::National,
+        }
+    }
+}
+
+impl std::fmt::Display for Kieswet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Kieswet::Of1918 => write!(f, "the rules of 1918"),
+            Kieswet::Of1922 => write!(f, "the rules of 1922"),
+            Kieswet::Of1925 => write!(f, "the rules of 1925-1933"),
+            Kieswet::Of1937 => write!(f, "the rules of 1937-1972"),
+            Kieswet::Of1977 => write!(f, "the rules of 1977-2012, with list combinations"),
+            Kieswet::Of2017 => write!(f, "the rules since 2017"),
+        }
+    }
+}
+
+/// An identifier of an election as used by the Kiesraad: a category, a year or a full date, and
+/// optionally a region, e.g. "TK20210317", "GR20260318_Gemeente" or "GR2022_Amsterdam"
+///
+/// ```
+/// use kiesraad_model::*;
+///
+/// let id = "TK19250701".parse::<ElectionCode>().unwrap();
+/// assert_eq!(id.body, Body::TweedeKamer);
+/// assert_eq!(id.kieswet(), Kieswet::Of1925);
+/// assert_eq!(id.method(100, false), Method::Bongaerts);
+/// assert_eq!(id.seats(), Some(100));
+///
+/// let id = "GR2022_Amsterdam".parse::<ElectionCode>().unwrap();
+/// assert_eq!(id.region.as_deref(), Some("Amsterdam"));
+/// assert_eq!(id.method(45, false), Method::Averages);
+///
+/// let id = "GR20140319".parse::<ElectionCode>().unwrap();
+/// assert_eq!(id.method(15, false), Method::SurplusesBefore2017);
+/// assert_eq!(id.method(15, true), Method::CombinedBefore2017);
+///
+/// assert!("EK20230530".parse::<ElectionCode>().is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ElectionCode {
+    pub body: Body,
+    pub year: u16,
+    /// The month and day of the election, if they are part of the identifier
+    pub date: Option<(u8, u8)>,
+    /// The region in which the body is elected, if it is part of the identifier
+    pub region: Option<String>,
+}
+
+impl ElectionCode {
+    /// The rules for apportioning seats in force at the time of the election
+    pub fn kieswet(&self) -> Kieswet {
+        Kieswet::on(self.year, self.date)
+    }
+
+    /// The method by which the seats are apportioned, for a body with the given number of seats
+    pub fn method(&self, seats: Count, combinations: bool) -> Method {
+        if self.body.is_national() {
+            self.kieswet().national_method(combinations)
+        } else if self.kieswet() >= Kieswet::Of2017 {
+            Method::for_seats(seats)
+        } else if combinations {
+            Method::CombinedBefore2017
+        } else if seats >= 19 {
+            Method::Averages
+        } else {
+            Method::SurplusesBefore2017
+        }
+    }
+
+    /// The number of seats of the body, if it is fixed by law; for other bodies it depends on the
+    /// number of inhabitants, or is determined separately for every election
+    pub fn seats(&self) -> Option<Count> {
+        match self.body {
+            // the Tweede Kamer was enlarged after the election of 1956
+            Body::TweedeKamer if self.year <= 1956 => Some(100),
+            Body::TweedeKamer => Some(150),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ElectionCode {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<ElectionCode, String> {
+        let (code, region) = match id.split_once('_') {
+            Some((code, region)) => (code, Some(region.to_string())),
+            None => (id, None),
+        };
+        let category = code.get(..2).unwrap_or_default();
+        let body = Body::from_category(category)
+            .ok_or_else(|| format!("unknown kind of election: {category}"))?;
+        let digits = &code[category.len()..];
+        let number = |range: std::ops::Range<usize>| {
+            digits
+                .get(range)
+                .filter(|x| x.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|x| x.parse::<u16>().ok())
+                .ok_or_else(|| format!("not an election date: {digits}"))
+        };
+        let date = match digits.len() {
+            4 => None,
+            8 => match (number(4..6)?, number(6..8)?) {
+                (month @ 1..=12, day @ 1..=31) => Some((month as u8, day as u8)),
+                _ => return Err(format!("not an election date: {digits}")),
+            },
+            _ => return Err(format!("not an election date: {digits}")),
+        };
+
+        Ok(ElectionCode {
+            body,
+            year: number(0..4)?,
+            date,
+            region,
+        })
+    }
+}
+
+impl std::fmt::Display for ElectionCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let category = match self.body {
+            Body::TweedeKamer => "TK",
+            Body::EuropeesParlement => "EP",
+            Body::ProvincialeStaten => "PS",
+            Body::Waterschap => "WS",
+            Body::Gemeenteraad => "GR",
+            Body::Eilandsraad => "ER",
+        };
+        write!(f, "{category}{:04}", self.year)?;
+        if let Some((month, day)) = self.date {
+            write!(f, "{month:02}{day:02}")?;
+        }
+        if let Some(region) = &self.region {
+            write!(f, "_{region}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1071 @@
+use crate::*;
+
+/// The output formats in which a report can be rendered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Markdown,
+    Html,
+}
+
+struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+struct Section {
+    title: String,
+    text: Vec<String>,
+    table: Option<Table>,
+}
+
+/// A report of a seat apportionment, modelled after the attachment to the official report
+/// ("proces-verbaal") of a central polling station, which lists the quota, the whole seats, the
+/// apportionment of rest seats and the final distribution of seats.
+pub struct Report {
+    title: String,
+    /// The list number of every party
+    lists: Vec<String>,
+    sections: Vec<Section>,
+}
+
+/// Display a fraction as a mixed number, as is done in official reports: `1234 8/19`
+fn mixed(fraction: Fraction) -> String {
+    let fraction = fraction.reduce();
+    let whole = fraction.numerator / fraction.denominator;
+    match fraction.numerator % fraction.denominator {
+        0 => whole.to_string(),
+        rest if whole == 0 => format!("{rest}/{}", fraction.denominator),
+        rest => format!("{whole} {rest}/{}", fraction.denominator),
+    }
+}
+
+/// The surplus of a party over a given number of seats, which may be negative, or `None` if it
+/// is too large to be represented
+fn surplus(votes: Count, seats: Count, quota: Fraction) -> Option<String> {
+    let votes = Fraction::from(votes);
+    let used = Fraction::from(seats).checked_mul(quota)?;
+    if votes >= used {
+        votes.checked_sub(used).map(mixed)
+    } else {
+        used.checked_sub(votes).map(|x| format!("-{}", mixed(x)))
+    }
+}
+
+/// A row in the tables of the apportionment between lists, where a combination of lists (or the
+/// lists of a party) takes part as a single list, represented in the trace by its first member
+enum Entry {
+    List(usize),
+    Group(Vec<usize>),
+}
+
+impl Entry {
+    /// The party that represents this entry in the trace
+    fn party(&self) -> usize {
+        match self {
+            Entry::List(i) => *i,
+            Entry::Group(members) => members[0],
+        }
+    }
+}
+
+struct RoundState {
+    round: Option<Round>,
+    start: Vec<Count>,
+    standings: Vec<Vec<(usize, Fraction)>>,
+    winners: Vec<usize>,
+    notes: Vec<String>,
+}
+
+impl Report {
+    /// Create a report from the `trace` of a seat apportionment between parties with the given
+    /// `names` and `votes`, resulting in the given `seats`.
+    pub fn new(
+        title: &str,
+        names: &[String],
+        votes: &[Votes],
+        seats: &[Seats],
+        trace: &[Event],
+    ) -> Report {
+        let parties = (0..votes.len())
+            .map(|i| PartySeats {
+                list: i + 1,
+                name: names.get(i).cloned().unwrap_or_default(),
+                votes: votes[i],
+                seats: seats[i],
+            })
+            .collect::<Vec<_>>();
+        Report::of_parties(title, &parties, trace)
+    }
+
+    /// Create a report of the `outcome` of an election
+    pub fn for_outcome(title: &str, outcome: &Outcome) -> Report {
+        Report::of_parties(title, &outcome.parties, &outcome.trace)
+    }
+
+    fn of_parties(title: &str, parties: &[PartySeats], trace: &[Event]) -> Report {
+        let names = parties.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+        let votes = parties.iter().map(|x| x.votes).collect::<Vec<_>>();
+        let seats = parties.iter().map(|x| x.seats).collect::<Vec<_>>();
+        let vote_count = votes.iter().map(|Votes(count)| count).sum::<Count>();
+        let seat_count = seats.iter().map(|x| x.count()).sum::<Count>();
+        // the quota that was used, which is the Hare quota unless the trace says otherwise
+        let quota = trace
+            .iter()
+            .find_map(|event| match event {
+                Event::Quota(quota) => Some(*quota),
+                _ => None,
+            })
+            .unwrap_or_else(|| frac(vote_count, seat_count.max(1)));
+        let list = |i: usize| parties[i].list.to_string();
+        // in running text, lists are also referred to by name
+        let label = |i: usize| match parties[i].name.as_str() {
+            "" => list(i),
+            name => format!("{} ({name})", list(i)),
+        };
+
+        let groups = trace
+            .iter()
+            .filter_map(|event| match event {
+                Event::Round(
+                    Round::Combination { members, .. } | Round::PartyLists { lists: members, .. },
+                ) => Some(members.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let entries = (0..votes.len())
+            .filter_map(
+                |i| match groups.iter().find(|members| members.contains(&i)) {
+                    Some(members) if members[0] == i => Some(Entry::Group(members.clone())),
+                    Some(_) => None,
+                    None => Some(Entry::List(i)),
+                },
+            )
+            .collect::<Vec<_>>();
+        // the list numbers, names and votes of an entry
+        let describe = |entry: &Entry| match entry {
+            Entry::List(i) => (list(*i), names[*i].clone(), votes[*i].0),
+            Entry::Group(members) => (
+                members
+                    .iter()
+                    .map(|&i| list(i))
+                    .collect::<Vec<_>>()
+                    .join("+"),
+                members
+                    .iter()
+                    .map(|&i| names[i].as_str())
+                    .filter(|name| !name.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" / "),
+                members.iter().map(|&i| votes[i].0).sum(),
+            ),
+        };
+        // until the seats of the combinations are divided, a combination is referred to by the
+        // lists in it
+        let entry_label = |i: usize, split: bool| match groups.iter().find(|x| x[0] == i) {
+            Some(members) if !split => {
+                let (number, name, _) = describe(&Entry::Group(members.clone()));
+                match name.as_str() {
+                    "" => number,
+                    name => format!("{number} ({name})"),
+                }
+            }
+            _ => label(i),
+        };
+        let within = match groups.first() {
+            Some(_)
+                if trace
+                    .iter()
+                    .any(|x| matches!(x, Event::Round(Round::PartyLists { .. }))) =>
+            {
+                "Seats within party"
+            }
+            _ => "Seats within combination",
+        };
+
+        let mut sections = vec![Section {
+            title: "Quota (kiesdeler)".to_string(),
+            text: vec![format!(
+                "The total number of valid votes cast on the lists is {vote_count}. With {seat_count} \
+                 seats to be apportioned, the quota is {}.",
+                mixed(quota)
+            )],
+            table: None,
+        }];
+
+        let mut whole = vec![0; votes.len()];
+        let mut current = vec![0; votes.len()];
+        let mut rounds = Vec::<RoundState>::new();
+        let mut notes = Vec::new();
+        let mut split = false;
+        let mut bonus = None;
+
+        for event in trace {
+            let round = |rounds: &mut Vec<RoundState>, current: &[Count]| {
+                if rounds.is_empty() {
+                    rounds.push(RoundState {
+                        round: None,
+                        start: current.to_vec(),
+                        standings: Vec::new(),
+                        winners: Vec::new(),
+                        notes: Vec::new(),
+                    });
+                }
+            };
+            match event {
+                Event::Quota(_) => {}
+                Event::WholeSeats { party, seats } => {
+                    whole[*party] = *seats;
+                    current[*party] = *seats;
+                }
+                Event::Round(kind) => {
+                    split |= matches!(kind, Round::Combination { .. } | Round::PartyLists { .. });
+                    rounds.push(RoundState {
+                        round: Some(kind.clone()),
+                        start: current.clone(),
+                        standings: Vec::new(),
+                        winners: Vec::new(),
+                        notes: Vec::new(),
+                    })
+                }
+                Event::Standings(standings) => {
+                    round(&mut rounds, &current);
+                    rounds.last_mut().unwrap().standings.push(standings.clone());
+                }
+                Event::RestSeat { party, .. } => {
+                    round(&mut rounds, &current);
+                    rounds.last_mut().unwrap().winners.push(*party);
+                    current[*party] += 1;
+                }
+                Event::MajorityBonus { party } => {
+                    current[*party] += 1;
+                    bonus = Some(*party);
+                    notes.push(format!(
+                        "List {} has an absolute majority of the votes, and received a seat before \
+                         the rest seats were apportioned.",
+                        entry_label(*party, split)
+                    ));
+                }
+                Event::MajorityCorrection { from, to } => {
+                    current[*from] -= 1;
+                    current[*to] += 1;
+                    notes.push(format!(
+                        "List {} has an absolute majority of the votes, but not of the seats. \
+                         The last rest seat awarded to list {} is therefore given to list {}.",
+                        entry_label(*to, split),
+                        entry_label(*from, split),
+                        entry_label(*to, split)
+                    ));
+                }
+                Event::Exhausted { party } => {
+                    let note = format!(
+                        "List {} has no more candidates and is not eligible for further seats.",
+                        entry_label(*party, split)
+                    );
+                    match rounds.last_mut() {
+                        Some(round) => round.notes.push(note),
+                        None => notes.push(note),
+                    }
+                }
+                Event::Lot { tied, winner } => {
+                    round(&mut rounds, &current);
+                    rounds.last_mut().unwrap().notes.push(format!(
+                        "Lots were drawn between lists {}; list {} won.",
+                        tied.iter()
+                            .map(|&i| entry_label(i, split))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        entry_label(*winner, split)
+                    ));
+                }
+                Event::RunnerUp { party, .. } => {
+                    if let Some(round) = rounds.last_mut() {
+                        round.notes.push(format!(
+                            "List {} is next in line for a seat.",
+                            entry_label(*party, split)
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut text = vec![
+            "Every list receives as many seats as the quota is contained in its number of votes."
+                .to_string(),
+        ];
+        let mut header = ["List", "Name", "Votes", "Whole seats"]
+            .map(String::from)
+            .to_vec();
+        if !groups.is_empty() {
+            text.push(
+                "A combination of lists takes part as a single list; it is followed by the division \
+                 of its seats over its members."
+                    .to_string(),
+            );
+            header.push(within.to_string());
+        }
+        let mut rows = Vec::new();
+        for entry in &entries {
+            let (number, name, count) = describe(entry);
+            let mut row = vec![
+                number,
+                name,
+                count.to_string(),
+                whole[entry.party()].to_string(),
+            ];
+            if let Entry::Group(members) = entry {
+                row.push(String::new());
+                rows.push(row);
+                for &i in members {
+                    rows.push(vec![
+                        list(i),
+                        names[i].clone(),
+                        votes[i].0.to_string(),
+                        String::new(),
+                        seats[i].count().to_string(),
+                    ]);
+                }
+            } else {
+                if !groups.is_empty() {
+                    row.push(String::new());
+                }
+                rows.push(row);
+            }
+        }
+        sections.push(Section {
+            title: "Whole seats".to_string(),
+            text,
+            table: Some(Table { header, rows }),
+        });
+
+        for state in rounds {
+            let eligible = |i: usize| {
+                state
+                    .standings
+                    .first()
+                    .is_some_and(|standings| standings.iter().any(|(party, _)| *party == i))
+            };
+            let awarded = |i: usize| state.winners.iter().filter(|&&w| w == i).count();
+            // the seats at the start of the round that are paid for by the votes of a list, i.e.
+            // without the seat for an absolute majority
+            let counted = |i: usize| state.start[i] - Count::from(bonus == Some(i));
+
+            let section =
+                match &state.round {
+                    Some(
+                        Round::Surplus
+                        | Round::SecondSurplus
+                        | Round::FurtherSurplus(_)
+                        | Round::BelowThreshold,
+                    ) => Section {
+                        title: match state.round {
+                            Some(Round::Surplus) => "Rest seats by largest surpluses".to_string(),
+                            Some(Round::SecondSurplus) => {
+                                "Second round of largest surpluses".to_string()
+                            }
+                            Some(Round::FurtherSurplus(round)) => {
+                                format!("Round {round} of largest surpluses")
+                            }
+                            _ => "Rest seats by largest surpluses of lists below the threshold"
+                                .to_string(),
+                        },
+                        text: vec![
+                            "Lists that are not eligible for a rest seat in this round are marked \
+                         with a dash."
+                                .to_string(),
+                        ],
+                        table: Some(Table {
+                            header: ["List", "Name", "Votes", "Surplus", "Rest seats"]
+                                .map(String::from)
+                                .to_vec(),
+                            rows: entries
+                                .iter()
+                                .map(|entry| {
+                                    let i = entry.party();
+                                    let (number, name, count) = describe(entry);
+                                    vec![
+                                        number,
+                                        name,
+                                        count.to_string(),
+                                        surplus(count, counted(i), quota)
+                                            .unwrap_or_else(|| "?".to_string()),
+                                        if eligible(i) {
+                                            awarded(i).to_string()
+                                        } else {
+                                            "-".to_string()
+                                        },
+                                    ]
+                                })
+                                .collect(),
+                        }),
+                    },
+                    Some(
+                        Round::Combination { members, seats }
+                        | Round::PartyLists {
+                            lists: members,
+                            seats,
+                        },
+                    ) => Section {
+                        title: if matches!(state.round, Some(Round::Combination { .. })) {
+                            "Apportionment within a list combination"
+                        } else {
+                            "Apportionment over the lists of a party"
+                        }
+                        .to_string(),
+                        text: vec![format!(
+                            "The {seats} seats awarded to {} {} are divided over these lists by \
+                         largest surpluses.",
+                            if matches!(state.round, Some(Round::Combination { .. })) {
+                                "the combination of lists"
+                            } else {
+                                "the party with lists"
+                            },
+                            members
+                                .iter()
+                                .map(|&i| list(i))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        )],
+                        table: Some(Table {
+                            header: ["List", "Name", "Votes", "Seats"]
+                                .map(String::from)
+                                .to_vec(),
+                            rows: members
+                                .iter()
+                                .map(|&i| {
+                                    vec![
+                                        list(i),
+                                        names[i].clone(),
+                                        votes[i].0.to_string(),
+                                        awarded(i).to_string(),
+                                    ]
+                                })
+                                .collect(),
+                        }),
+                    },
+                    kind => Section {
+                        title: match kind {
+                            Some(Round::UnrestrictedAverages) => {
+                                "Rest seats by largest averages, without restrictions"
+                            }
+                            _ => "Rest seats by largest averages",
+                        }
+                        .to_string(),
+                        text: vec![
+                        "For every rest seat, the average number of votes per seat that a list \
+                         would have if it received that seat is listed. The list with the largest \
+                         average, marked with an asterisk, receives the seat. Lists that are not \
+                         eligible are marked with a dash."
+                            .to_string(),
+                    ],
+                        table: Some(Table {
+                            header: ["List", "Name", "Seats"]
+                                .map(String::from)
+                                .into_iter()
+                                .chain((1..=state.standings.len()).map(|n| format!("Seat {n}")))
+                                .collect(),
+                            rows: entries
+                                .iter()
+                                .map(|entry| {
+                                    let i = entry.party();
+                                    let (number, name, _) = describe(entry);
+                                    let mut row = vec![number, name, state.start[i].to_string()];
+                                    for (standings, winner) in state.standings.iter().zip(
+                                        state.winners.iter().map(Some).chain(iter::repeat(None)),
+                                    ) {
+                                        row.push(
+                                            match standings.iter().find(|(party, _)| *party == i) {
+                                                Some((_, quality)) if winner == Some(&i) => {
+                                                    format!("{} *", mixed(*quality))
+                                                }
+                                                Some((_, quality)) => mixed(*quality),
+                                                None => "-".to_string(),
+                                            },
+                                        );
+                                    }
+                                    row
+                                })
+                                .collect(),
+                        }),
+                    },
+                };
+            sections.push(Section {
+                text: section.text.into_iter().chain(state.notes).collect(),
+                ..section
+            });
+        }
+
+        if !notes.is_empty() {
+            sections.push(Section {
+                title: "Absolute majority".to_string(),
+                text: notes,
+                table: None,
+            });
+        }
+
+        sections.push(Section {
+            title: "Distribution of seats".to_string(),
+            text: Vec::new(),
+            table: Some(Table {
+                header: ["List", "Name", "Votes", "Seats"]
+                    .map(String::from)
+                    .to_vec(),
+                rows: (0..votes.len())
+                    .map(|i| {
+                        vec![
+                            list(i),
+                            names[i].clone(),
+                            votes[i].0.to_string(),
+                            seats[i].count().to_string(),
+                        ]
+                    })
+                    .collect(),
+            }),
+        });
+
+        Report {
+            title: title.to_string(),
+            lists: parties.iter().map(|x| x.list.to_string()).collect(),
+            sections,
+        }
+    }
+
+    /// Add the `elected` candidates of every list, out of its `candidates`, to the report
+    pub fn with_elected(
+        mut self,
+        candidates: &[Vec<Candidate>],
+        elected: &[Vec<Elected>],
+    ) -> Report {
+        let rows = elected
+            .iter()
+            .flatten()
+            .map(|x| {
+                let candidate = &candidates[x.list][x.position];
+                vec![
+                    self.lists
+                        .get(x.list)
+                        .cloned()
+                        .unwrap_or((x.list + 1).to_string()),
+                    candidate.name.clone(),
+                    (x.position + 1).to_string(),
+                    candidate.votes.0.to_string(),
+                    if x.preference { "*" } else { "" }.to_string(),
+                ]
+            })
+            .collect();
+        self.sections.push(Section {
+            title: "Elected candidates".to_string(),
+            text: vec![
+                "The seats of every list are filled by the candidates whose preference votes meet \
+                 the threshold, marked with an asterisk, and then by the other candidates in the \
+                 order of the list."
+                    .to_string(),
+            ],
+            table: Some(Table {
+                header: ["List", "Name", "Position", "Preference votes", "Preference"]
+                    .map(String::from)
+                    .to_vec(),
+                rows,
+            }),
+        });
+        self
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.render_text(),
+            ReportFormat::Markdown => self.render_markdown(),
+            ReportFormat::Html => self.render_html(),
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = format!(
+            "{}\n{}\n",
+            self.title,
+            "=".repeat(self.title.chars().count())
+        );
+        for section in &self.sections {
+            out += &format!(
+                "\n{}\n{}\n",
+                section.title,
+                "-".repeat(section.title.chars().count())
+            );
+            for text in &section.text {
+                out += &format!("{text}\n");
+            }
+            if let Some(table) = &section.table {
+                let mut widths = table
+                    .header
+                    .iter()
+                    .map(|x| x.chars().count())
+                    .collect::<Vec<_>>();
+                for row in &table.rows {
+                    for (width, cell) in widths.iter_mut().zip(row) {
+                        *width = (*width).max(cell.chars().count());
+                    }
+                }
+                let line = |row: &[String]| {
+                    row.iter()
+                        .zip(&widths)
+                        .enumerate()
+                        .map(|(n, (cell, width))| {
+                            // names are left-aligned, numbers right-aligned
+                            if n == 1 {
+                                format!("{cell:<width$}")
+                            } else {
+                                format!("{cell:>width$}")
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                        .trim_end()
+                        .to_string()
+                };
+                out += "\n";
+                out += &line(&table.header);
+                out += "\n";
+                for row in &table.rows {
+                    out += &line(row);
+                    out += "\n";
+                }
+            }
+        }
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.title);
+        for section in &self.sections {
+            out += &format!("\n## {}\n", section.title);
+            for text in &section.text {
+                out += &format!("\n{text}\n");
+            }
+            if let Some(table) = &section.table {
+                let cell = |x: &String| x.replace('|', "\\|").replace('*', "\\*");
+                out += &format!(
+                    "\n| {} |\n",
+                    table
+                        .header
+                        .iter()
+                        .map(cell)
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                );
+                out += &format!(
+                    "|{}\n",
+                    (0..table.header.len())
+                        .map(|n| if n == 1 { " --- |" } else { " ---: |" })
+                        .collect::<String>()
+                );
+                for row in &table.rows {
+                    out += &format!(
+                        "| {} |\n",
+                        row.iter().map(cell).collect::<Vec<_>>().join(" | ")
+                    );
+                }
+            }
+        }
+        out
+    }
+
+    fn render_html(&self) -> String {
+        fn escape(text: &str) -> String {
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        }
+
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n\
+             <body>\n<h1>{0}</h1>\n",
+            escape(&self.title)
+        );
+        for section in &self.sections {
+            out += &format!("<h2>{}</h2>\n", escape(&section.title));
+            for text in &section.text {
+                out += &format!("<p>{}</p>\n", escape(text));
+            }
+            if let Some(table) = &section.table {
+                out += "<table>\n<tr>";
+                for cell in &table.header {
+                    out += &format!("<th>{}</th>", escape(cell));
+                }
+                out += "</tr>\n";
+                for row in &table.rows {
+                    out += "<tr>";
+                    for cell in row {
+                        out += &format!("<td>{}</td>", escape(cell));
+                    }
+                    out += "</tr>\n";
+                }
+                out += "</table>\n";
+            }
+        }
+        out += "</body>\n</html>\n";
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surpluses_over_the_quota_that_was_used() {
+        let votes = [Votes(4700), Votes(2800), Votes(1700), Votes(800)];
+        let mut seats = [Seats::unlimited(); 4];
+        let mut trace = Vec::new();
+        let mut ballot = LowestListNumber;
+        let mut ctx = Context::observed(&mut ballot, &mut trace);
+        let method = QuotaMethod {
+            quota: Quota::Droop,
+            ..QuotaMethod::DUTCH
+        };
+        allocate_per_quota(method, Seats::filled(7), &votes, &mut seats, &mut ctx).unwrap();
+
+        let report =
+            Report::new("Droop", &[], &votes, &seats, &trace).render(ReportFormat::Markdown);
+        assert!(report.contains("the quota is 1251."), "{report}");
+        assert!(report.contains("| 1 |  | 4700 | 947 | 1 |"), "{report}");
+        assert!(report.contains("| 4 |  | 800 | 800 | - |"), "{report}");
+    }
+
+    #[test]
+    fn surpluses_of_large_votes() {
+        // the products of these votes and the denominator of the quota do not fit in a `Count`
+        let k = Count::MAX / 8;
+        let votes = [Votes(5 * k), Votes(2 * k), Votes(k)];
+        let seats = [Seats::filled(2), Seats::filled(1), Seats::filled(0)];
+        let quota = frac(8 * k, 3);
+        let trace = [
+            Event::Quota(quota),
+            Event::WholeSeats { party: 0, seats: 1 },
+            Event::Round(Round::Surplus),
+            Event::RestSeat {
+                party: 0,
+                quality: frac(7 * k, 3),
+            },
+            Event::RestSeat {
+                party: 1,
+                quality: frac(2 * k, 1),
+            },
+        ];
+
+        let report =
+            Report::new("Large", &[], &votes, &seats, &trace).render(ReportFormat::Markdown);
+        let row = format!("| 1 |  | {} | 5380300354831952552 1/3 | - |", 5 * k);
+        assert!(report.contains(&row), "{report}");
+        let row = format!("| 2 |  | {} | {} | - |", 2 * k, 2 * k);
+        assert!(report.contains(&row), "{report}");
+    }
+
+    fn council(
+        votes: &[Count],
+        candidates: &[Count],
+        total_seats: Count,
+        rules: Interpretation,
+    ) -> Report {
+        let names = ["A", "B", "C", "D", "E"].map(String::from);
+        let votes = votes.iter().map(|&x| Votes(x)).collect::<Vec<_>>();
+        let mut seats = candidates
+            .iter()
+            .map(|&x| Seats::limited(x))
+            .collect::<Vec<_>>();
+        let mut trace = Vec::new();
+        let mut ballot = LowestListNumber;
+        let mut ctx = Context::observed(&mut ballot, &mut trace).with_rules(rules);
+        allocate(Seats::filled(total_seats), &votes, &mut seats, &mut ctx).unwrap();
+        Report::new("Council", &names, &votes, &seats, &trace)
+    }
+
+    #[test]
+    fn majority_correction_and_exhaustion() {
+        // list 4 is exhausted after its whole seat, and list 1 receives the last rest seat of
+        // list 3 because of its absolute majority of the votes
+        let report = council(
+            &[505, 185, 180, 130],
+            &[9, 9, 9, 1],
+            9,
+            Interpretation::Literal,
+        );
+        assert_eq!(
+            report.render(ReportFormat::Text),
+            r#"Council
+=======
+
+Quota (kiesdeler)
+-----------------
+The total number of valid votes cast on the lists is 1000. With 9 seats to be apportioned, the quota is 111 1/9.
+
+Whole seats
+-----------
+Every list receives as many seats as the quota is contained in its number of votes.
+
+List  Name  Votes  Whole seats
+   1  A       505            4
+   2  B       185            1
+   3  C       180            1
+   4  D       130            1
+
+Rest seats by largest surpluses
+-------------------------------
+Lists that are not eligible for a rest seat in this round are marked with a dash.
+List 3 (C) is next in line for a seat.
+
+List  Name  Votes  Surplus  Rest seats
+   1  A       505   60 5/9           0
+   2  B       185   73 8/9           1
+   3  C       180   68 8/9           1
+   4  D       130   18 8/9           -
+
+Absolute majority
+-----------------
+List 4 (D) has no more candidates and is not eligible for further seats.
+List 1 (A) has an absolute majority of the votes, but not of the seats. The last rest seat awarded to list 3 (C) is therefore given to list 1 (A).
+
+Distribution of seats
+---------------------
+
+List  Name  Votes  Seats
+   1  A       505      5
+   2  B       185      2
+   3  C       180      1
+   4  D       130      1
+"#
+        );
+        assert_eq!(
+            report.render(ReportFormat::Markdown),
+            r#"# Council
+
+## Quota (kiesdeler)
+
+The total number of valid votes cast on the lists is 1000. With 9 seats to be apportioned, the quota is 111 1/9.
+
+## Whole seats
+
+Every list receives as many seats as the quota is contained in its number of votes.
+
+| List | Name | Votes | Whole seats |
+| ---: | --- | ---: | ---: |
+| 1 | A | 505 | 4 |
+| 2 | B | 185 | 1 |
+| 3 | C | 180 | 1 |
+| 4 | D | 130 | 1 |
+
+## Rest seats by largest surpluses
+
+Lists that are not eligible for a rest seat in this round are marked with a dash.
+
+List 3 (C) is next in line for a seat.
+
+| List | Name | Votes | Surplus | Rest seats |
+| ---: | --- | ---: | ---: | ---: |
+| 1 | A | 505 | 60 5/9 | 0 |
+| 2 | B | 185 | 73 8/9 | 1 |
+| 3 | C | 180 | 68 8/9 | 1 |
+| 4 | D | 130 | 18 8/9 | - |
+
+## Absolute majority
+
+List 4 (D) has no more candidates and is not eligible for further seats.
+
+List 1 (A) has an absolute majority of the votes, but not of the seats. The last rest seat awarded to list 3 (C) is therefore given to list 1 (A).
+
+## Distribution of seats
+
+| List | Name | Votes | Seats |
+| ---: | --- | ---: | ---: |
+| 1 | A | 505 | 5 |
+| 2 | B | 185 | 2 |
+| 3 | C | 180 | 1 |
+| 4 | D | 130 | 1 |
+"#
+        );
+        assert_eq!(
+            report.render(ReportFormat::Html),
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Council</title>
+</head>
+<body>
+<h1>Council</h1>
+<h2>Quota (kiesdeler)</h2>
+<p>The total number of valid votes cast on the lists is 1000. With 9 seats to be apportioned, the quota is 111 1/9.</p>
+<h2>Whole seats</h2>
+<p>Every list receives as many seats as the quota is contained in its number of votes.</p>
+<table>
+<tr><th>List</th><th>Name</th><th>Votes</th><th>Whole seats</th></tr>
+<tr><td>1</td><td>A</td><td>505</td><td>4</td></tr>
+<tr><td>2</td><td>B</td><td>185</td><td>1</td></tr>
+<tr><td>3</td><td>C</td><td>180</td><td>1</td></tr>
+<tr><td>4</td><td>D</td><td>130</td><td>1</td></tr>
+</table>
+<h2>Rest seats by largest surpluses</h2>
+<p>Lists that are not eligible for a rest seat in this round are marked with a dash.</p>
+<p>List 3 (C) is next in line for a seat.</p>
+<table>
+<tr><th>List</th><th>Name</th><th>Votes</th><th>Surplus</th><th>Rest seats</th></tr>
+<tr><td>1</td><td>A</td><td>505</td><td>60 5/9</td><td>0</td></tr>
+<tr><td>2</td><td>B</td><td>185</td><td>73 8/9</td><td>1</td></tr>
+<tr><td>3</td><td>C</td><td>180</td><td>68 8/9</td><td>1</td></tr>
+<tr><td>4</td><td>D</td><td>130</td><td>18 8/9</td><td>-</td></tr>
+</table>
+<h2>Absolute majority</h2>
+<p>List 4 (D) has no more candidates and is not eligible for further seats.</p>
+<p>List 1 (A) has an absolute majority of the votes, but not of the seats. The last rest seat awarded to list 3 (C) is therefore given to list 1 (A).</p>
+<h2>Distribution of seats</h2>
+<table>
+<tr><th>List</th><th>Name</th><th>Votes</th><th>Seats</th></tr>
+<tr><td>1</td><td>A</td><td>505</td><td>5</td></tr>
+<tr><td>2</td><td>B</td><td>185</td><td>2</td></tr>
+<tr><td>3</td><td>C</td><td>180</td><td>1</td></tr>
+<tr><td>4</td><td>D</td><td>130</td><td>1</td></tr>
+</table>
+</body>
+</html>
+"#
+        );
+    }
+
+    #[test]
+    fn unrestricted_averages() {
+        // list 1 is below the surplus threshold and list 2 is exhausted, so the rest seats are
+        // awarded by averages, and the last one without restrictions
+        let report = council(&[43, 267], &[4, 3], 5, Interpretation::Kiesraad);
+        assert_eq!(
+            report.render(ReportFormat::Text),
+            r#"Council
+=======
+
+Quota (kiesdeler)
+-----------------
+The total number of valid votes cast on the lists is 310. With 5 seats to be apportioned, the quota is 62.
+
+Whole seats
+-----------
+Every list receives as many seats as the quota is contained in its number of votes.
+
+List  Name  Votes  Whole seats
+   1  A        43            0
+   2  B       267            3
+
+Rest seats by largest surpluses
+-------------------------------
+Lists that are not eligible for a rest seat in this round are marked with a dash.
+
+List  Name  Votes  Surplus  Rest seats
+   1  A        43       43           -
+   2  B       267       81           -
+
+Rest seats by largest averages
+------------------------------
+For every rest seat, the average number of votes per seat that a list would have if it received that seat is listed. The list with the largest average, marked with an asterisk, receives the seat. Lists that are not eligible are marked with a dash.
+
+List  Name  Seats  Seat 1  Seat 2
+   1  A         0    43 *       -
+   2  B         3       -       -
+
+Rest seats by largest averages, without restrictions
+----------------------------------------------------
+For every rest seat, the average number of votes per seat that a list would have if it received that seat is listed. The list with the largest average, marked with an asterisk, receives the seat. Lists that are not eligible are marked with a dash.
+List 1 (A) is next in line for a seat.
+
+List  Name  Seats    Seat 1
+   1  A         1  21 1/2 *
+   2  B         3         -
+
+Absolute majority
+-----------------
+List 2 (B) has no more candidates and is not eligible for further seats.
+
+Distribution of seats
+---------------------
+
+List  Name  Votes  Seats
+   1  A        43      2
+   2  B       267      3
+"#
+        );
+    }
+
+    #[test]
+    fn list_combination() {
+        let names = ["A", "B", "C", "D", "E"].map(String::from);
+        let votes = [340, 155, 145, 190, 170].map(Votes);
+        let mut seats = [Seats::unlimited(); 5];
+        let mut trace = Vec::new();
+        let mut ballot = LowestListNumber;
+        let mut ctx = Context::observed(&mut ballot, &mut trace);
+        let combinations = [vec![1, 2]];
+        allocate_combined_before_2017(
+            Seats::filled(9),
+            &votes,
+            &mut seats,
+            &combinations,
+            &mut ctx,
+        )
+        .unwrap();
+
+        let report = Report::new("Council", &names, &votes, &seats, &trace);
+        assert_eq!(
+            report.render(ReportFormat::Markdown),
+            r#"# Council
+
+## Quota (kiesdeler)
+
+The total number of valid votes cast on the lists is 1000. With 9 seats to be apportioned, the quota is 111 1/9.
+
+## Whole seats
+
+Every list receives as many seats as the quota is contained in its number of votes.
+
+A combination of lists takes part as a single list; it is followed by the division of its seats over its members.
+
+| List | Name | Votes | Whole seats | Seats within combination |
+| ---: | --- | ---: | ---: | ---: |
+| 1 | A | 340 | 3 |  |
+| 2+3 | B / C | 300 | 2 |  |
+| 2 | B | 155 |  | 2 |
+| 3 | C | 145 |  | 1 |
+| 4 | D | 190 | 1 |  |
+| 5 | E | 170 | 1 |  |
+
+## Rest seats by largest surpluses
+
+Lists that are not eligible for a rest seat in this round are marked with a dash.
+
+List 5 (E) is next in line for a seat.
+
+| List | Name | Votes | Surplus | Rest seats |
+| ---: | --- | ---: | ---: | ---: |
+| 1 | A | 340 | 6 2/3 | 0 |
+| 2+3 | B / C | 300 | 77 7/9 | 1 |
+| 4 | D | 190 | 78 8/9 | 1 |
+| 5 | E | 170 | 58 8/9 | 0 |
+
+## Apportionment within a list combination
+
+The 3 seats awarded to the combination of lists 2, 3 are divided over these lists by largest surpluses.
+
+| List | Name | Votes | Seats |
+| ---: | --- | ---: | ---: |
+| 2 | B | 155 | 2 |
+| 3 | C | 145 | 1 |
+
+## Distribution of seats
+
+| List | Name | Votes | Seats |
+| ---: | --- | ---: | ---: |
+| 1 | A | 340 | 3 |
+| 2 | B | 155 | 2 |
+| 3 | C | 145 | 1 |
+| 4 | D | 190 | 2 |
+| 5 | E | 170 | 1 |
+"#
+        );
+    }
+}
@@ -0,0 +1,54 @@
+/// The choices made in a seat apportionment where the Kieswet leaves room for interpretation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    /// Lists that received no votes at all are not eligible for rest seats
+    pub exclude_zero_votes: bool,
+    /// The absolute majority correction of article P 9 is determined as if no list is exhausted,
+    /// i.e. before applying article P 10, and in bodies of less than 19 seats the extra seat is
+    /// awarded before the surpluses
+    pub majority_before_exhaustion: bool,
+    /// Seats that remain after the largest surpluses and averages rounds in bodies of less than 19
+    /// seats are awarded by unrestricted largest averages, instead of remaining unoccupied
+    pub unrestricted_averages: bool,
+}
+
+/// A consistent reading of the Kieswet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Interpretation {
+    /// The literal text of the law
+    #[default]
+    Literal,
+    /// The reading of the Kiesraad, as implemented in its software (OSV and Abacus)
+    Kiesraad,
+}
+
+impl Interpretation {
+    pub fn rules(self) -> Rules {
+        match self {
+            Interpretation::Literal => Rules::default(),
+            Interpretation::Kiesraad => Rules {
+                exclude_zero_votes: true,
+                majority_before_exhaustion: true,
+                unrestricted_averages: true,
+            },
+        }
+    }
+}
+
+impl From<Interpretation> for Rules {
+    fn from(interpretation: Interpretation) -> Rules {
+        interpretation.rules()
+    }
+}
+
+impl std::fmt::Display for Interpretation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Interpretation::Literal => write!(f, "the literal text of the Kieswet"),
+            Interpretation::Kiesraad => write!(f, "the reading of the Kiesraad"),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+/// A change in the membership of a body that affects the seats of a list
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// A member leaves the body permanently, e.g. by resigning or being appointed as alderman
+    Vacancy(String),
+    /// A candidate does not accept their appointment
+    Declined(String),
+    /// A member takes temporary leave (for pregnancy or illness) and is replaced
+    Leave(String),
+    /// A member returns from temporary leave, which ends their replacement
+    Return(String),
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Change::Vacancy(name) => write!(f, "{name} leaves"),
+            Change::Declined(name) => write!(f, "{name} declines the appointment"),
+            Change::Leave(name) => write!(f, "{name} takes leave"),
+            Change::Return(name) => write!(f, "{name} returns from leave"),
+        }
+    }
+}
+
+/// The consequences of a change in membership
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Succession {
+    /// A candidate is appointed to fill a vacant seat
+    Appointed(String),
+    /// A candidate is appointed to temporarily replace a member on leave
+    Replacement(String),
+    /// A temporary replacement has ended, and the replacement is a candidate again
+    ReplacementEnded(String),
+    /// The list has no more candidates, so the seat stays vacant
+    Vacant,
+}
+
+/// The change referred to someone who does not hold a seat in the required capacity
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotAMember(pub String);
+
+impl std::fmt::Display for NotAMember {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{} does not hold a seat that can be changed this way",
+            self.0
+        )
+    }
+}
+
+/// The seats of a single list in a body, and the candidates that will succeed its members.
+#[derive(Clone, Debug)]
+pub struct ListSeats {
+    ranking: Vec<String>,
+    members: Vec<String>,
+    on_leave: Vec<(String, Option<String>)>,
+    passed_over: Vec<String>,
+    vacant: usize,
+}
+
+impl ListSeats {
+    /// A list that obtained a number of `seats`, with its candidates in order of `ranking` (see
+    /// the `ranking` function for the order that follows from preference votes).
+    pub fn new(ranking: Vec<String>, seats: usize) -> Self {
+        let members = ranking.iter().take(seats).cloned().collect::<Vec<_>>();
+        let vacant = seats - members.len();
+        ListSeats {
+            ranking,
+            members,
+            on_leave: Vec::new(),
+            passed_over: Vec::new(),
+            vacant,
+        }
+    }
+
+    /// The members of the list, which includes members that are on leave
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    /// The temporary replacements of members that are on leave
+    pub fn replacements(&self) -> impl Iterator<Item = &String> {
+        self.on_leave.iter().filter_map(|(_, x)| x.as_ref())
+    }
+
+    /// The number of seats that could not be filled
+    pub fn vacant(&self) -> usize {
+        self.vacant
+    }
+
+    fn is_replacement(&self, name: &str) -> bool {
+        self.replacements().any(|x| x == name)
+    }
+
+    /// The next candidate in line: members, former members, and candidates that declined an
+    /// earlier appointment are passed over. A temporary replacement is still in line for a permanent seat.
+    fn next_in_line(&self, replacements: bool) -> Option<String> {
+        self.ranking
+            .iter()
+            .find(|x| {
+                !self.members.contains(x)
+                    && !self.passed_over.contains(x)
+                    && (replacements || !self.is_replacement(x))
+            })
+            .cloned()
+    }
+
+    /// Fill a vacant seat, which can be done by a temporary replacement, in which case the member
+    /// they replaced is in need of a new replacement.
+    fn fill_vacancy(&mut self) -> Vec<Succession> {
+        let Some(successor) = self.next_in_line(true) else {
+            self.vacant += 1;
+            return vec![Succession::Vacant];
+        };
+
+        self.members.push(successor.clone());
+        let mut result = vec![Succession::Appointed(successor.clone())];
+
+        if let Some((absent, _)) = self
+            .on_leave
+            .iter()
+            .find(|(_, x)| x.as_ref() == Some(&successor))
+            .cloned()
+        {
+            result.extend(self.replace(&absent));
+        }
+
+        result
+    }
+
+    /// Appoint a new temporary replacement for a member that is on leave
+    fn replace(&mut self, absent: &str) -> Vec<Succession> {
+        let replacement = self.next_in_line(false);
+        let result = match &replacement {
+            Some(x) => Succession::Replacement(x.clone()),
+            None => Succession::Vacant,
+        };
+        for (member, x) in &mut self.on_leave {
+            if member == absent {
+                *x = replacement.clone();
+            }
+        }
+        vec![result]
+    }
+
+    fn remove(&mut self, name: &str) -> Result<(), NotAMember> {
+        let i = self
+            .members
+            .iter()
+            .position(|x| x == name)
+            .ok_or_else(|| NotAMember(name.to_string()))?;
+        self.members.remove(i);
+        Ok(())
+    }
+
+    /// Process a change in membership, resulting in the appointments that follow from it.
+    pub fn apply(&mut self, change: &Change) -> Result<Vec<Succession>, NotAMember> {
+        match change {
+            Change::Vacancy(name) => {
+                self.remove(name)?;
+                self.passed_over.push(name.clone());
+                // the replacement of a member on leave is no longer needed
+                self.on_leave.retain(|(member, _)| member != name);
+                Ok(self.fill_vacancy())
+            }
+            Change::Declined(name) => {
+                // a temporary replacement that declines only needs to be replaced in turn
+                if let Some((absent, _)) = self
+                    .on_leave
+                    .iter()
+                    .find(|(_, x)| x.as_ref() == Some(name))
+                    .cloned()
+                {
+                    self.passed_over.push(name.clone());
+                    return Ok(self.replace(&absent));
+                }
+                self.remove(name)?;
+                self.passed_over.push(name.clone());
+                Ok(self.fill_vacancy())
+            }
+            Change::Leave(name) => {
+                if !self.members.contains(name) || self.on_leave.iter().any(|(x, _)| x == name) {
+                    return Err(NotAMember(name.clone()));
+                }
+                self.on_leave.push((name.clone(), None));
+                Ok(self.replace(name))
+            }
+            Change::Return(name) => {
+                let i = self
+                    .on_leave
+                    .iter()
+                    .position(|(x, _)| x == name)
+                    .ok_or_else(|| NotAMember(name.clone()))?;
+                let (_, replacement) = self.on_leave.remove(i);
+                Ok(replacement
+                    .into_iter()
+                    .map(Succession::ReplacementEnded)
+                    .collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(ranking: &[&str], seats: usize) -> ListSeats {
+        ListSeats::new(ranking.iter().map(|x| x.to_string()).collect(), seats)
+    }
+
+    fn apply(list: &mut ListSeats, change: Change) -> Vec<Succession> {
+        list.apply(&change).unwrap()
+    }
+
+    fn vacancy(name: &str) -> Change {
+        Change::Vacancy(name.to_string())
+    }
+
+    fn declined(name: &str) -> Change {
+        Change::Declined(name.to_string())
+    }
+
+    fn leave(name: &str) -> Change {
+        Change::Leave(name.to_string())
+    }
+
+    fn back(name: &str) -> Change {
+        Change::Return(name.to_string())
+    }
+
+    fn appointed(name: &str) -> Succession {
+        Succession::Appointed(name.to_string())
+    }
+
+    fn replacement(name: &str) -> Succession {
+        Succession::Replacement(name.to_string())
+    }
+
+    #[test]
+    fn vacancy_is_filled_by_the_next_candidate() {
+        let mut seats = list(&["A", "B", "C", "D"], 2);
+        assert_eq!(apply(&mut seats, vacancy("A")), [appointed("C")]);
+        assert_eq!(seats.members(), ["B", "C"]);
+
+        // a former member is not appointed again
+        assert_eq!(apply(&mut seats, vacancy("C")), [appointed("D")]);
+        assert_eq!(seats.members(), ["B", "D"]);
+    }
+
+    #[test]
+    fn declined_appointment() {
+        let mut seats = list(&["A", "B", "C", "D"], 2);
+        assert_eq!(apply(&mut seats, declined("B")), [appointed("C")]);
+        assert_eq!(apply(&mut seats, vacancy("A")), [appointed("D")]);
+        assert_eq!(seats.members(), ["C", "D"]);
+    }
+
+    #[test]
+    fn member_returns_from_leave() {
+        let mut seats = list(&["A", "B", "C", "D"], 2);
+        assert_eq!(apply(&mut seats, leave("A")), [replacement("C")]);
+        assert_eq!(seats.members(), ["A", "B"]);
+        assert!(seats.replacements().eq(["C"]));
+
+        let ended = Succession::ReplacementEnded("C".to_string());
+        assert_eq!(apply(&mut seats, back("A")), [ended]);
+        assert_eq!(seats.replacements().count(), 0);
+
+        // the former replacement is first in line again
+        assert_eq!(apply(&mut seats, vacancy("B")), [appointed("C")]);
+    }
+
+    #[test]
+    fn declined_replacement() {
+        let mut seats = list(&["A", "B", "C", "D", "E"], 2);
+        assert_eq!(apply(&mut seats, leave("A")), [replacement("C")]);
+        assert_eq!(apply(&mut seats, declined("C")), [replacement("D")]);
+        assert!(seats.replacements().eq(["D"]));
+
+        // the replacement takes the vacant seat, and is replaced in turn
+        let result = apply(&mut seats, vacancy("B"));
+        assert_eq!(result, [appointed("D"), replacement("E")]);
+        assert_eq!(seats.members(), ["A", "D"]);
+        assert!(seats.replacements().eq(["E"]));
+    }
+
+    #[test]
+    fn vacancy_of_a_member_on_leave() {
+        let mut seats = list(&["A", "B", "C", "D"], 2);
+        assert_eq!(apply(&mut seats, leave("A")), [replacement("C")]);
+        assert_eq!(apply(&mut seats, vacancy("A")), [appointed("C")]);
+        assert_eq!(seats.members(), ["B", "C"]);
+        assert_eq!(seats.replacements().count(), 0);
+    }
+
+    #[test]
+    fn exhausted_list() {
+        let mut seats = list(&["A", "B", "C"], 2);
+        assert_eq!(apply(&mut seats, vacancy("A")), [appointed("C")]);
+        assert_eq!(apply(&mut seats, vacancy("B")), [Succession::Vacant]);
+        assert_eq!(seats.members(), ["C"]);
+        assert_eq!(seats.vacant(), 1);
+
+        let mut seats = list(&["A", "B"], 2);
+        assert_eq!(apply(&mut seats, leave("A")), [Succession::Vacant]);
+        assert_eq!(seats.replacements().count(), 0);
+        assert_eq!(apply(&mut seats, back("A")), []);
+
+        let seats = list(&["A"], 2);
+        assert_eq!(seats.members(), ["A"]);
+        assert_eq!(seats.vacant(), 1);
+    }
+
+    #[test]
+    fn changes_of_non_members() {
+        let mut seats = list(&["A", "B", "C", "D"], 2);
+        let not_a_member = |name: &str| Err(NotAMember(name.to_string()));
+        assert_eq!(seats.apply(&vacancy("C")), not_a_member("C"));
+        assert_eq!(seats.apply(&declined("C")), not_a_member("C"));
+        assert_eq!(seats.apply(&back("A")), not_a_member("A"));
+        assert_eq!(seats.apply(&leave("Z")), not_a_member("Z"));
+
+        apply(&mut seats, leave("A"));
+        assert_eq!(seats.apply(&leave("A")), not_a_member("A"));
+        assert_eq!(seats.members(), ["A", "B"]);
+    }
+}
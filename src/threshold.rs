@@ -0,0 +1,53 @@
+use crate::data::*;
+
+/// An electoral threshold: the votes that a party needs to be eligible for any seats at all
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Threshold {
+    /// A fraction of the quota, i.e. of the votes divided by the seats
+    Quota(Fraction),
+    /// A percentage of the votes
+    Percentage(Fraction),
+    /// A number of votes
+    Votes(Count),
+    /// A number of seats, i.e. a multiple of the quota
+    Seats(Count),
+}
+
+impl Threshold {
+    /// The smallest number of votes that meets the threshold, in an apportionment of `seat_count`
+    /// seats over parties with `vote_count` votes in total; `None` if there are no seats or it
+    /// cannot be represented
+    ///
+    /// ```
+    /// use kiesraad_model::*;
+    ///
+    /// assert_eq!(Threshold::Seats(2).minimum(15000, 150), Some(frac(200, 1)));
+    /// assert_eq!(Threshold::Percentage(frac(3, 1)).minimum(15000, 150), Some(frac(450, 1)));
+    /// assert_eq!(Threshold::Quota(frac(3, 4)).minimum(15000, 150), Some(frac(75, 1)));
+    /// assert_eq!(Threshold::Votes(1000).minimum(15000, 150), Some(frac(1000, 1)));
+    /// ```
+    pub fn minimum(self, vote_count: Count, seat_count: Count) -> Option<Fraction> {
+        let quota = Fraction::new(vote_count, seat_count).ok()?;
+        match self {
+            Threshold::Quota(share) => share.checked_mul(quota),
+            Threshold::Percentage(percentage) => percentage.checked_mul(frac(vote_count, 100)),
+            Threshold::Votes(votes) => Some(frac(votes, 1)),
+            Threshold::Seats(seats) => frac(seats, 1).checked_mul(quota),
+        }
+    }
+}
+
+impl std::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Threshold::Quota(share) => write!(f, "{share} of the quota"),
+            Threshold::Percentage(percentage) => write!(f, "{percentage}% of the votes"),
+            Threshold::Votes(1) => write!(f, "1 vote"),
+            Threshold::Votes(votes) => write!(f, "{votes} votes"),
+            Threshold::Seats(1) => write!(f, "1 seat"),
+            Threshold::Seats(seats) => write!(f, "{seats} seats"),
+        }
+    }
+}
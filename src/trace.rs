@@ -0,0 +1,197 @@
+use crate::data::*;
+
+/// The steps taken during a seat apportionment. Parties are identified by their index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The number of votes needed for a whole seat (the "kiesdeler")
+    Quota(Fraction),
+    /// A party received whole seats
+    WholeSeats { party: usize, seats: Count },
+    /// A new round of rest seat apportionment is started
+    Round(Round),
+    /// The quality of every party that is eligible for the next rest seat
+    Standings(Vec<(usize, Fraction)>),
+    /// A party received a rest seat, based on the given quality
+    RestSeat { party: usize, quality: Fraction },
+    /// A party with an absolute majority of votes received a seat up front, before the rest
+    /// seats were apportioned
+    MajorityBonus { party: usize },
+    /// A seat was taken from one party and given to a party with an absolute majority of votes
+    MajorityCorrection { from: usize, to: usize },
+    /// A party has no more candidates to fill seats with
+    Exhausted { party: usize },
+    /// Lots were drawn between parties that were tied for a seat
+    Lot { tied: Vec<usize>, winner: usize },
+    /// The party that would have received the next seat in a round
+    RunnerUp { party: usize, quality: Fraction },
+}
+
+/// The kinds of rounds in which rest seats are apportioned
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Round {
+    /// Rest seats are apportioned by largest surplus
+    Surplus,
+    /// Rest seats are apportioned by largest surplus for the second time
+    SecondSurplus,
+    /// Rest seats are apportioned by largest surplus for the given time, after the second
+    FurtherSurplus(Count),
+    /// Rest seats are apportioned by largest surplus to parties below the threshold
+    BelowThreshold,
+    /// Rest seats are apportioned by largest averages
+    Averages,
+    /// Rest seats are apportioned by largest averages, without restrictions
+    UnrestrictedAverages,
+    /// The seats of a list combination are divided over its members
+    Combination { members: Vec<usize>, seats: Count },
+    /// The seats of a party are divided over the lists it submitted in the kieskringen
+    PartyLists { lists: Vec<usize>, seats: Count },
+}
+
+impl Event {
+    /// Change the party indices mentioned in this event
+    pub fn map_parties(self, f: impl Fn(usize) -> usize) -> Event {
+        match self {
+            Event::WholeSeats { party, seats } => Event::WholeSeats {
+                party: f(party),
+                seats,
+            },
+            Event::Round(Round::Combination { members, seats }) => {
+                Event::Round(Round::Combination {
+                    members: members.into_iter().map(f).collect(),
+                    seats,
+                })
+            }
+            Event::Standings(standings) => Event::Standings(
+                standings
+                    .into_iter()
+                    .map(|(party, quality)| (f(party), quality))
+                    .collect(),
+            ),
+            Event::RestSeat { party, quality } => Event::RestSeat {
+                party: f(party),
+                quality,
+            },
+            Event::MajorityBonus { party } => Event::MajorityBonus { party: f(party) },
+            Event::MajorityCorrection { from, to } => Event::MajorityCorrection {
+                from: f(from),
+                to: f(to),
+            },
+            Event::Exhausted { party } => Event::Exhausted { party: f(party) },
+            Event::Lot { tied, winner } => Event::Lot {
+                tied: tied.into_iter().map(&f).collect(),
+                winner: f(winner),
+            },
+            Event::RunnerUp { party, quality } => Event::RunnerUp {
+                party: f(party),
+                quality,
+            },
+            Event::Round(Round::PartyLists { lists, seats }) => Event::Round(Round::PartyLists {
+                lists: lists.into_iter().map(f).collect(),
+                seats,
+            }),
+            event @ (Event::Quota(_) | Event::Round(_)) => event,
+        }
+    }
+}
+
+impl Event {
+    /// Describe this event, referring to every party as "list" followed by its label, e.g.
+    /// `3 (VVD)`; parties without a label are referred to by their list number
+    pub fn describe(&self, labels: &[String]) -> String {
+        let list = |i: usize| labels.get(i).cloned().unwrap_or((i + 1).to_string());
+        let lists = |parties: &[usize]| {
+            parties
+                .iter()
+                .map(|&i| list(i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Event::Quota(quota) => format!("quota: {quota}"),
+            Event::WholeSeats { party, seats } => {
+                format!("whole seats for list {}: {seats}", list(*party))
+            }
+            Event::Round(Round::Surplus) => "continuing by surpluses".to_string(),
+            Event::Round(Round::SecondSurplus) => {
+                "entering second round of surplus apportionment".to_string()
+            }
+            Event::Round(Round::FurtherSurplus(round)) => {
+                format!("entering round {round} of surplus apportionment")
+            }
+            Event::Round(Round::BelowThreshold) => {
+                "continuing by surpluses of lists below the threshold".to_string()
+            }
+            Event::Round(Round::Averages) => "continuing by averages".to_string(),
+            Event::Round(Round::UnrestrictedAverages) => {
+                "continuing by unrestricted averages".to_string()
+            }
+            Event::Round(Round::Combination { members, seats }) => format!(
+                "dividing {seats} seats over the combination of lists {}",
+                lists(members)
+            ),
+            Event::Round(Round::PartyLists {
+                lists: members,
+                seats,
+            }) => format!(
+                "dividing {seats} seats of a party over its lists {}",
+                lists(members)
+            ),
+            Event::Standings(standings) => format!(
+                "standings: {}",
+                standings
+                    .iter()
+                    .map(|(party, quality)| format!("list {} [{quality}]", list(*party)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Event::RestSeat { party, quality } => {
+                format!("rest seat for list {} [{quality}]", list(*party))
+            }
+            Event::MajorityBonus { party } => {
+                format!("awarding a majority bonus seat to list {}", list(*party))
+            }
+            Event::MajorityCorrection { from, to } => format!(
+                "an absolute majority correction moved a seat from list {} to list {}",
+                list(*from),
+                list(*to)
+            ),
+            Event::Exhausted { party } => format!("list {} is exhausted", list(*party)),
+            Event::Lot { tied, winner } => format!(
+                "lot drawn between lists {}: won by list {}",
+                lists(tied),
+                list(*winner)
+            ),
+            Event::RunnerUp { party, quality } => {
+                format!("no rest seat for list {} [{quality}]", list(*party))
+            }
+        }
+    }
+}
+
+/// Lists are numbered starting from one, so that is how parties are displayed.
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.describe(&[]))
+    }
+}
+
+/// Something that wants to follow the steps of a seat apportionment as they happen.
+pub trait Observer {
+    fn event(&mut self, event: Event);
+}
+
+/// Collect all the events of a seat apportionment
+impl Observer for Vec<Event> {
+    fn event(&mut self, event: Event) {
+        self.push(event);
+    }
+}
+
+/// Print all the events of a seat apportionment to standard error
+pub struct Printer;
+
+impl Observer for Printer {
+    fn event(&mut self, event: Event) {
+        eprintln!("{event}");
+    }
+}